@@ -0,0 +1,119 @@
+use anyhow::{anyhow, Result};
+use regex::Regex;
+use std::sync::Mutex;
+
+use crate::config::AutoTagRule;
+
+/// 已编译的自动标签规则
+pub struct CompiledRule {
+    regex: Regex,
+    tag: String,
+}
+
+/// 编译规则列表，无效的正则会被跳过并输出警告
+pub fn compile_rules(rules: &[AutoTagRule]) -> Vec<CompiledRule> {
+    rules
+        .iter()
+        .filter_map(|rule| match Regex::new(&rule.pattern) {
+            Ok(regex) => Some(CompiledRule {
+                regex,
+                tag: rule.tag.clone(),
+            }),
+            Err(err) => {
+                eprintln!(
+                    "Skipping invalid auto-tag pattern {:?}: {err}",
+                    rule.pattern
+                );
+                None
+            }
+        })
+        .collect()
+}
+
+/// 校验规则中的正则是否都能编译，保存配置前调用以便把错误反馈给用户
+pub fn validate_rules(rules: &[AutoTagRule]) -> Result<()> {
+    for rule in rules {
+        if let Err(err) = Regex::new(&rule.pattern) {
+            return Err(anyhow!(
+                "Invalid auto-tag pattern {:?} for tag {:?}: {err}",
+                rule.pattern,
+                rule.tag
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// 返回内容命中的标签（按规则顺序去重）
+pub fn tags_for(rules: &[CompiledRule], content: &str) -> Vec<String> {
+    let mut tags: Vec<String> = Vec::new();
+    for rule in rules {
+        if rule.regex.is_match(content) && !tags.contains(&rule.tag) {
+            tags.push(rule.tag.clone());
+        }
+    }
+    tags
+}
+
+/// 缓存编译结果，只有规则变化时才重新编译
+pub struct AutoTagger {
+    cache: Mutex<(Vec<AutoTagRule>, Vec<CompiledRule>)>,
+}
+
+impl AutoTagger {
+    pub fn new() -> Self {
+        Self {
+            cache: Mutex::new((Vec::new(), Vec::new())),
+        }
+    }
+
+    pub fn tags_for(&self, rules: &[AutoTagRule], content: &str) -> Vec<String> {
+        let mut cache = self.cache.lock().unwrap();
+        if cache.0 != rules {
+            *cache = (rules.to_vec(), compile_rules(rules));
+        }
+        tags_for(&cache.1, content)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rule(pattern: &str, tag: &str) -> AutoTagRule {
+        AutoTagRule {
+            pattern: pattern.into(),
+            tag: tag.into(),
+        }
+    }
+
+    #[test]
+    fn compile_skips_invalid_patterns() {
+        let rules = vec![rule(r"^https?://", "link"), rule("(unclosed", "broken")];
+        let compiled = compile_rules(&rules);
+
+        assert_eq!(compiled.len(), 1);
+        assert_eq!(tags_for(&compiled, "https://example.com"), vec!["link"]);
+        assert!(tags_for(&compiled, "plain text").is_empty());
+    }
+
+    #[test]
+    fn tags_are_deduplicated_in_rule_order() {
+        let rules = vec![
+            rule(r"\d+", "number"),
+            rule("TODO", "todo"),
+            rule(r"\d{3}", "number"),
+        ];
+        let compiled = compile_rules(&rules);
+
+        assert_eq!(tags_for(&compiled, "TODO 123"), vec!["number", "todo"]);
+    }
+
+    #[test]
+    fn validate_reports_invalid_pattern() {
+        assert!(validate_rules(&[rule("^ok$", "ok")]).is_ok());
+
+        let err = validate_rules(&[rule("^ok$", "ok"), rule("[a-", "bad")]).unwrap_err();
+        assert!(err.to_string().contains("[a-"));
+    }
+}
@@ -0,0 +1,217 @@
+use std::fmt;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// 批次的最长持续时间；前端出错未调用 `end` 时，超时后自动恢复逐条事件
+const BATCH_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// 批量操作期间暂停逐条的历史更新事件。
+/// 支持嵌套：只有最外层批次结束（或超时作废）时才调用一次刷新回调。
+pub struct EventBatch {
+    state: Mutex<BatchState>,
+    /// 发送汇总刷新事件，参数为批次期间被抑制的事件数量
+    refresh: Box<dyn Fn(usize) + Send + Sync>,
+}
+
+impl fmt::Debug for EventBatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("EventBatch")
+            .field("state", &self.state)
+            .finish_non_exhaustive()
+    }
+}
+
+#[derive(Debug, Default)]
+struct BatchState {
+    depth: usize,
+    suppressed: usize,
+    /// 最外层批次开始的时间
+    started: Option<Instant>,
+}
+
+impl BatchState {
+    /// 批次超时则整体作废，之后的事件照常发送。
+    /// 返回作废批次中被抑制的事件数量（非零时调用方需要补发刷新事件）。
+    fn expire(&mut self, now: Instant) -> Option<usize> {
+        let timed_out = self
+            .started
+            .is_some_and(|started| now.duration_since(started) >= BATCH_TIMEOUT);
+        if self.depth > 0 && timed_out {
+            eprintln!(
+                "Event batch was not ended within {BATCH_TIMEOUT:?}; resuming history events"
+            );
+            let suppressed = std::mem::take(self).suppressed;
+            return (suppressed > 0).then_some(suppressed);
+        }
+        None
+    }
+}
+
+impl EventBatch {
+    /// `refresh` 在最外层批次结束、或超时作废的批次抑制过事件时调用
+    pub fn new(refresh: impl Fn(usize) + Send + Sync + 'static) -> Self {
+        Self {
+            state: Mutex::default(),
+            refresh: Box::new(refresh),
+        }
+    }
+
+    /// 在释放锁之后调用刷新回调，避免回调中再次进入批次状态时死锁
+    fn flush(&self, suppressed: Option<usize>) {
+        if let Some(suppressed) = suppressed {
+            (self.refresh)(suppressed);
+        }
+    }
+
+    /// 进入一个批次
+    pub fn begin(&self) {
+        self.begin_at(Instant::now());
+    }
+
+    fn begin_at(&self, now: Instant) {
+        let expired = {
+            let mut state = self.state.lock().unwrap();
+            let expired = state.expire(now);
+            if state.depth == 0 {
+                state.started = Some(now);
+            }
+            state.depth += 1;
+            expired
+        };
+        self.flush(expired);
+    }
+
+    /// 结束一个批次；最外层批次结束时调用一次刷新回调并返回期间被抑制的事件数量，
+    /// 否则返回 None。没有进行中的批次时调用不产生任何效果。
+    pub fn end(&self) -> Option<usize> {
+        self.end_at(Instant::now())
+    }
+
+    fn end_at(&self, now: Instant) -> Option<usize> {
+        let (expired, ended) = {
+            let mut state = self.state.lock().unwrap();
+            let expired = state.expire(now);
+            if state.depth == 0 {
+                (expired, None)
+            } else {
+                state.depth -= 1;
+                if state.depth > 0 {
+                    (None, None)
+                } else {
+                    state.started = None;
+                    (None, Some(std::mem::take(&mut state.suppressed)))
+                }
+            }
+        };
+        self.flush(expired);
+        self.flush(ended);
+        ended
+    }
+
+    /// 判断事件是否应当立即发送；批次进行中时记为已抑制并返回 false
+    pub fn should_emit(&self) -> bool {
+        self.should_emit_at(Instant::now())
+    }
+
+    fn should_emit_at(&self, now: Instant) -> bool {
+        let (expired, emit) = {
+            let mut state = self.state.lock().unwrap();
+            let expired = state.expire(now);
+            if state.depth == 0 {
+                (expired, true)
+            } else {
+                state.suppressed += 1;
+                (expired, false)
+            }
+        };
+        self.flush(expired);
+        emit
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+
+    /// 创建一个批次，并返回记录刷新回调调用次数与最近一次参数的计数器
+    fn counting_batch() -> (EventBatch, Arc<AtomicUsize>, Arc<AtomicUsize>) {
+        let refreshes = Arc::new(AtomicUsize::new(0));
+        let last = Arc::new(AtomicUsize::new(0));
+        let batch = EventBatch::new({
+            let refreshes = Arc::clone(&refreshes);
+            let last = Arc::clone(&last);
+            move |suppressed| {
+                refreshes.fetch_add(1, Ordering::SeqCst);
+                last.store(suppressed, Ordering::SeqCst);
+            }
+        });
+        (batch, refreshes, last)
+    }
+
+    #[test]
+    fn emits_outside_batches() {
+        let (batch, refreshes, _) = counting_batch();
+        assert!(batch.should_emit());
+        assert_eq!(batch.end(), None);
+        assert!(batch.should_emit());
+        assert_eq!(refreshes.load(Ordering::SeqCst), 0);
+    }
+
+    #[test]
+    fn nested_batches_flush_once() {
+        let (batch, refreshes, last) = counting_batch();
+        batch.begin();
+        assert!(!batch.should_emit());
+
+        batch.begin();
+        assert!(!batch.should_emit());
+        assert!(!batch.should_emit());
+        assert_eq!(batch.end(), None);
+        assert_eq!(refreshes.load(Ordering::SeqCst), 0);
+
+        assert_eq!(batch.end(), Some(3));
+        assert_eq!(refreshes.load(Ordering::SeqCst), 1);
+        assert_eq!(last.load(Ordering::SeqCst), 3);
+
+        assert!(batch.should_emit());
+        assert_eq!(batch.end(), None);
+        assert_eq!(refreshes.load(Ordering::SeqCst), 1);
+    }
+
+    #[test]
+    fn abandoned_batch_times_out() {
+        let (batch, refreshes, last) = counting_batch();
+        let start = Instant::now();
+        batch.begin_at(start);
+        batch.begin_at(start);
+        assert!(!batch.should_emit_at(start + Duration::from_secs(1)));
+        assert!(!batch.should_emit_at(start + Duration::from_secs(2)));
+
+        // 超时作废时补发一次刷新，携带被抑制的事件数量
+        assert!(batch.should_emit_at(start + BATCH_TIMEOUT));
+        assert_eq!(refreshes.load(Ordering::SeqCst), 1);
+        assert_eq!(last.load(Ordering::SeqCst), 2);
+        assert_eq!(batch.end_at(start + BATCH_TIMEOUT), None);
+        assert_eq!(refreshes.load(Ordering::SeqCst), 1);
+
+        // 超时后可以重新开始新的批次
+        let later = start + BATCH_TIMEOUT * 2;
+        batch.begin_at(later);
+        assert!(!batch.should_emit_at(later));
+        assert_eq!(batch.end_at(later), Some(1));
+        assert_eq!(refreshes.load(Ordering::SeqCst), 2);
+    }
+
+    #[test]
+    fn expired_batch_without_suppressed_events_does_not_refresh() {
+        let (batch, refreshes, _) = counting_batch();
+        let start = Instant::now();
+        batch.begin_at(start);
+
+        assert!(batch.should_emit_at(start + BATCH_TIMEOUT));
+        assert_eq!(batch.end_at(start + BATCH_TIMEOUT), None);
+        assert_eq!(refreshes.load(Ordering::SeqCst), 0);
+    }
+}
@@ -1,13 +1,61 @@
-use anyhow::Result;
+use anyhow::{anyhow, Result};
 use serde::{Deserialize, Serialize};
 use std::fs;
-use std::path::PathBuf;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use crate::database::{Database, ExitClearAction, HistoryFilter};
 
 const MIN_HISTORY_LIMIT: i64 = 1;
 const MAX_HISTORY_LIMIT: i64 = 5_000;
+const MAX_MAINTENANCE_INTERVAL_HOURS: i64 = 24 * 30;
+const MAX_RETENTION_DAYS: i64 = 3_650;
+const MIN_TRASH_RETENTION_MINUTES: i64 = 1;
+const MAX_TRASH_RETENTION_MINUTES: i64 = 7 * 24 * 60;
+const MAX_RANKING_WEIGHT: f64 = 1_000.0;
+const MAX_TIMEZONE_OFFSET_MINUTES: i64 = 14 * 60;
+const CAPTURE_CONTENT_TYPES: [&str; 3] = ["text", "image", "file"];
+
+/// 自动标签规则：内容匹配 `pattern`（正则）时添加 `tag`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AutoTagRule {
+    pub pattern: String,
+    pub tag: String,
+}
+
+/// 快速筛选预设：按下 `key` 时应用 `filter`（格式见 `HistoryFilter::parse`）
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FilterPreset {
+    pub key: String,
+    pub filter: String,
+}
+
+/// 关闭或最小化主窗口时的行为
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WindowAction {
+    /// 隐藏到托盘并从任务栏移除
+    Hide,
+    /// 最小化到任务栏（适用于没有可用托盘的桌面环境）
+    Minimize,
+    /// 退出应用
+    Quit,
+}
+
+impl WindowAction {
+    pub fn parse(name: &str) -> Result<Self> {
+        match name.trim() {
+            "hide" => Ok(Self::Hide),
+            "minimize" => Ok(Self::Minimize),
+            "quit" => Ok(Self::Quit),
+            other => Err(anyhow!("Unsupported window action: {other}")),
+        }
+    }
+}
 
 /// 应用配置
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
 pub struct Config {
     /// 历史记录最大数量
     pub max_history_items: i64,
@@ -17,6 +65,56 @@ pub struct Config {
     pub theme: String,
     /// 全局快捷键
     pub hotkey: String,
+    /// 后台维护（清理、压缩、索引优化）的间隔小时数，0 表示关闭
+    pub maintenance_interval_hours: i64,
+    /// 非收藏记录的保留天数，0 表示不按时间清理
+    pub retention_days: i64,
+    /// 多个文件位于同一目录时，预览中只显示一次共同目录
+    pub fold_file_preview_paths: bool,
+    /// 不记录看起来像密码、API 密钥或 JWT 的文本
+    pub skip_secret_like: bool,
+    /// JSON 文本的预览显示为顶层结构摘要，如 `{…} 12 keys`
+    pub summarize_json_preview: bool,
+    /// 文本去除首尾空白后少于该字符数时不记录，0 表示全部记录
+    pub min_capture_length: usize,
+    /// 紧凑预览：去掉文本预览各行共同的前导缩进（不影响保存的内容）
+    pub compact_text_preview: bool,
+    /// 清空历史时是否先移入回收站
+    pub trash_enabled: bool,
+    /// 回收站记录可恢复的分钟数，超时后永久删除
+    pub trash_retention_minutes: i64,
+    /// 是否记录操作审计日志
+    pub enable_audit_log: bool,
+    /// 审计日志保留天数，0 表示不按时间清理
+    pub audit_log_retention_days: i64,
+    /// 剪切板同时包含位图和文件时优先保存哪一种："bitmap"（默认，保存图片本身）或 "file"
+    pub image_format_priority: String,
+    /// 捕获后按内容自动添加标签的规则
+    pub auto_tag_rules: Vec<AutoTagRule>,
+    /// 导入文本行时跳过历史中已存在的相同内容
+    pub skip_duplicate_imports: bool,
+    /// 额外捕获 X11/Wayland 的 PRIMARY 选区（选中即复制），仅 Linux 生效
+    pub capture_primary_selection: bool,
+    /// 综合排序中新近程度的权重（刚复制为满分，按天衰减）
+    pub ranking_recency_weight: f64,
+    /// 综合排序中每次从历史复制的加分
+    pub ranking_usage_weight: f64,
+    /// 综合排序中收藏的加分
+    pub ranking_favorite_weight: f64,
+    /// 按时段统计时使用的时区偏移（分钟，东八区为 480），为空时使用系统时区
+    pub timezone_offset_minutes: Option<i64>,
+    /// 允许记录的内容类型（"text"、"image"、"file"），不在列表中的剪切板内容直接丢弃
+    pub capture_allowlist: Vec<String>,
+    /// 数字键等快捷键对应的快速筛选
+    pub filter_presets: Vec<FilterPreset>,
+    /// 退出应用时清空历史（适用于共享电脑）
+    pub clear_on_exit: bool,
+    /// 退出时的清空范围："non_favorites"（保留收藏）或 "all"
+    pub clear_on_exit_mode: String,
+    /// 点击关闭按钮时的行为："hide"（默认）、"minimize" 或 "quit"
+    pub window_close_action: String,
+    /// 最小化窗口时的行为："minimize"（默认，保持系统行为）或 "hide"（隐藏到托盘）
+    pub window_minimize_action: String,
 }
 
 impl Default for Config {
@@ -26,6 +124,46 @@ impl Default for Config {
             auto_start: false,
             theme: "auto".to_string(),
             hotkey: "CommandOrControl+Shift+V".to_string(),
+            maintenance_interval_hours: 24,
+            retention_days: 0,
+            fold_file_preview_paths: true,
+            compact_text_preview: false,
+            min_capture_length: 0,
+            summarize_json_preview: false,
+            skip_secret_like: false,
+            trash_enabled: true,
+            trash_retention_minutes: 30,
+            enable_audit_log: false,
+            audit_log_retention_days: 30,
+            image_format_priority: "bitmap".to_string(),
+            auto_tag_rules: Vec::new(),
+            skip_duplicate_imports: true,
+            capture_primary_selection: false,
+            ranking_recency_weight: 10.0,
+            ranking_usage_weight: 2.0,
+            ranking_favorite_weight: 5.0,
+            timezone_offset_minutes: None,
+            capture_allowlist: CAPTURE_CONTENT_TYPES
+                .iter()
+                .map(|t| t.to_string())
+                .collect(),
+            filter_presets: [
+                ("1", "all"),
+                ("2", "favorites"),
+                ("3", "type:image"),
+                ("4", "type:text"),
+                ("5", "type:file"),
+            ]
+            .iter()
+            .map(|(key, filter)| FilterPreset {
+                key: key.to_string(),
+                filter: filter.to_string(),
+            })
+            .collect(),
+            clear_on_exit: false,
+            clear_on_exit_mode: "non_favorites".to_string(),
+            window_close_action: "hide".to_string(),
+            window_minimize_action: "minimize".to_string(),
         }
     }
 }
@@ -45,28 +183,50 @@ impl Config {
         }
     }
 
+    /// 读取磁盘上的原始配置（不做 sanitize），文件不存在时返回默认配置
+    pub fn load_raw(config_path: &Path) -> Result<Self> {
+        if config_path.exists() {
+            let content = fs::read_to_string(config_path)?;
+            Ok(serde_json::from_str(&content)?)
+        } else {
+            Ok(Config::default())
+        }
+    }
+
+    /// 返回 sanitize 后取值会发生变化的字段名（按字段名排序）
+    pub fn adjusted_fields(&self) -> Result<Vec<String>> {
+        let raw = serde_json::to_value(self)?;
+        let effective = serde_json::to_value(self.clone().sanitized())?;
+
+        let mut fields: Vec<String> = match (raw, effective) {
+            (serde_json::Value::Object(raw), serde_json::Value::Object(effective)) => raw
+                .into_iter()
+                .filter(|(key, value)| effective.get(key) != Some(value))
+                .map(|(key, _)| key)
+                .collect(),
+            _ => Vec::new(),
+        };
+        fields.sort();
+        Ok(fields)
+    }
+
     /// 保存配置
     pub fn save(&self, config_path: PathBuf) -> Result<()> {
         let mut sanitized = self.clone();
         sanitized.sanitize();
         let content = serde_json::to_string_pretty(&sanitized)?;
-        
+
         // 确保父目录存在
         if let Some(parent) = config_path.parent() {
             fs::create_dir_all(parent)?;
         }
-        
-        fs::write(config_path, content)?;
-        Ok(())
+
+        write_atomically(&config_path, &content)
     }
 
     /// 规范化配置中的字段，确保取值安全
     pub fn sanitize(&mut self) {
-        if self.max_history_items < MIN_HISTORY_LIMIT {
-            self.max_history_items = MIN_HISTORY_LIMIT;
-        } else if self.max_history_items > MAX_HISTORY_LIMIT {
-            self.max_history_items = MAX_HISTORY_LIMIT;
-        }
+        self.max_history_items = clamp_history_limit(self.max_history_items);
 
         if !matches!(self.theme.as_str(), "light" | "dark" | "auto") {
             self.theme = "auto".to_string();
@@ -75,6 +235,133 @@ impl Config {
         if self.hotkey.trim().is_empty() {
             self.hotkey = Config::default().hotkey;
         }
+
+        if WindowAction::parse(&self.window_close_action).is_err() {
+            self.window_close_action = "hide".to_string();
+        }
+        if !matches!(
+            WindowAction::parse(&self.window_minimize_action),
+            Ok(WindowAction::Hide | WindowAction::Minimize)
+        ) {
+            self.window_minimize_action = "minimize".to_string();
+        }
+
+        if ExitClearAction::parse(&self.clear_on_exit_mode).is_err() {
+            self.clear_on_exit_mode = "non_favorites".to_string();
+        }
+
+        if !matches!(self.image_format_priority.as_str(), "bitmap" | "file") {
+            self.image_format_priority = "bitmap".to_string();
+        }
+
+        for rule in &mut self.auto_tag_rules {
+            rule.tag = rule.tag.trim().to_string();
+        }
+        self.auto_tag_rules
+            .retain(|rule| !rule.pattern.is_empty() && !rule.tag.is_empty());
+
+        let mut allowlist: Vec<String> = Vec::new();
+        for content_type in &self.capture_allowlist {
+            let content_type = content_type.trim().to_lowercase();
+            if CAPTURE_CONTENT_TYPES.contains(&content_type.as_str())
+                && !allowlist.contains(&content_type)
+            {
+                allowlist.push(content_type);
+            }
+        }
+        self.capture_allowlist = allowlist;
+
+        let mut seen_keys: Vec<String> = Vec::new();
+        self.filter_presets.retain_mut(|preset| {
+            preset.key = preset.key.trim().to_string();
+            preset.filter = preset.filter.trim().to_string();
+            if preset.key.is_empty()
+                || seen_keys.contains(&preset.key)
+                || HistoryFilter::parse(&preset.filter).is_err()
+            {
+                return false;
+            }
+            seen_keys.push(preset.key.clone());
+            true
+        });
+
+        self.maintenance_interval_hours = self
+            .maintenance_interval_hours
+            .clamp(0, MAX_MAINTENANCE_INTERVAL_HOURS);
+        self.retention_days = self.retention_days.clamp(0, MAX_RETENTION_DAYS);
+        self.trash_retention_minutes = self
+            .trash_retention_minutes
+            .clamp(MIN_TRASH_RETENTION_MINUTES, MAX_TRASH_RETENTION_MINUTES);
+        self.audit_log_retention_days = self.audit_log_retention_days.clamp(0, MAX_RETENTION_DAYS);
+
+        self.timezone_offset_minutes = self
+            .timezone_offset_minutes
+            .map(|offset| offset.clamp(-MAX_TIMEZONE_OFFSET_MINUTES, MAX_TIMEZONE_OFFSET_MINUTES));
+
+        let defaults = Config::default();
+        for (weight, default) in [
+            (
+                &mut self.ranking_recency_weight,
+                defaults.ranking_recency_weight,
+            ),
+            (
+                &mut self.ranking_usage_weight,
+                defaults.ranking_usage_weight,
+            ),
+            (
+                &mut self.ranking_favorite_weight,
+                defaults.ranking_favorite_weight,
+            ),
+        ] {
+            *weight = if weight.is_finite() {
+                weight.clamp(0.0, MAX_RANKING_WEIGHT)
+            } else {
+                default
+            };
+        }
+    }
+
+    /// 点击关闭按钮时应执行的行为，无效取值按 "hide" 处理
+    pub fn close_action(&self) -> WindowAction {
+        WindowAction::parse(&self.window_close_action).unwrap_or(WindowAction::Hide)
+    }
+
+    /// 最小化窗口时应执行的行为，只会是 `Hide` 或 `Minimize`
+    pub fn minimize_action(&self) -> WindowAction {
+        match WindowAction::parse(&self.window_minimize_action) {
+            Ok(WindowAction::Hide) => WindowAction::Hide,
+            _ => WindowAction::Minimize,
+        }
+    }
+
+    /// 退出时需要执行的清空操作，未开启时返回 None
+    pub fn exit_clear_action(&self) -> Option<ExitClearAction> {
+        if !self.clear_on_exit {
+            return None;
+        }
+        Some(
+            ExitClearAction::parse(&self.clear_on_exit_mode)
+                .unwrap_or(ExitClearAction::NonFavorites),
+        )
+    }
+
+    /// 查找按键对应的筛选预设
+    pub fn filter_preset(&self, key: &str) -> Option<&FilterPreset> {
+        self.filter_presets
+            .iter()
+            .find(|preset| preset.key == key.trim())
+    }
+
+    /// 追加尚不存在的自动标签规则，返回新增数量
+    pub fn merge_auto_tag_rules(&mut self, rules: &[AutoTagRule]) -> usize {
+        let mut added = 0;
+        for rule in rules {
+            if !self.auto_tag_rules.contains(rule) {
+                self.auto_tag_rules.push(rule.clone());
+                added += 1;
+            }
+        }
+        added
     }
 
     /// 返回一个经过 sanitize 处理的配置副本
@@ -83,3 +370,263 @@ impl Config {
         self
     }
 }
+
+/// 将历史记录上限限制在允许的范围内
+pub fn clamp_history_limit(value: i64) -> i64 {
+    value.clamp(MIN_HISTORY_LIMIT, MAX_HISTORY_LIMIT)
+}
+
+/// 比较配置中的开机自启与系统实际状态，不一致时以系统为准返回新的配置值
+pub fn reconcile_autostart(configured: bool, actual: bool) -> Option<bool> {
+    if configured == actual {
+        None
+    } else {
+        Some(actual)
+    }
+}
+
+/// 将配置恢复为默认值：写入配置文件，并同步内存中的配置与数据库侧的设置。
+/// 历史记录、收藏与标签保持不变；开机自启与托盘菜单由调用方同步。
+pub fn restore_default_config(
+    db: &Database,
+    config: &Mutex<Config>,
+    config_path: PathBuf,
+) -> Result<Config> {
+    let default_config = Config::default().sanitized();
+    default_config.save(config_path)?;
+
+    *config.lock().map_err(|_| anyhow!("poisoned config"))? = default_config.clone();
+    db.set_audit_enabled(default_config.enable_audit_log);
+    db.set_capture_allowlist(&default_config.capture_allowlist);
+
+    Ok(default_config)
+}
+
+/// 校验会话内的临时上限：非正数直接拒绝，其余按配置上限的范围截断
+pub fn session_history_limit(value: i64) -> Result<i64> {
+    if value < MIN_HISTORY_LIMIT {
+        return Err(anyhow!(
+            "Session history limit must be at least {MIN_HISTORY_LIMIT}, got {value}"
+        ));
+    }
+    Ok(clamp_history_limit(value))
+}
+
+/// 会话内的临时上限优先于配置中的上限；无效的会话上限被忽略
+pub fn effective_history_limit(config_limit: i64, session_limit: Option<i64>) -> i64 {
+    session_limit
+        .filter(|limit| *limit >= MIN_HISTORY_LIMIT)
+        .unwrap_or(config_limit)
+}
+
+/// 先写入同目录下的临时文件并落盘，再原子地重命名覆盖目标文件，
+/// 保证任意时刻磁盘上都是完整的旧配置或新配置
+fn write_atomically(path: &Path, content: &str) -> Result<()> {
+    let file_name = path
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_else(|| "config.json".to_string());
+    let tmp_path = path.with_file_name(format!("{file_name}.tmp"));
+
+    let result = (|| -> Result<()> {
+        let mut file = fs::File::create(&tmp_path)?;
+        file.write_all(content.as_bytes())?;
+        file.sync_all()?;
+        fs::rename(&tmp_path, path)?;
+        Ok(())
+    })();
+
+    if result.is_err() {
+        let _ = fs::remove_file(&tmp_path);
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sanitize_normalizes_capture_allowlist() {
+        let mut config = Config {
+            capture_allowlist: vec![
+                " Text ".into(),
+                "bogus".into(),
+                "text".into(),
+                "file".into(),
+            ],
+            ..Config::default()
+        };
+        config.sanitize();
+
+        assert_eq!(config.capture_allowlist, vec!["text", "file"]);
+    }
+
+    #[test]
+    fn session_limit_rejects_non_positive_values() {
+        assert!(session_history_limit(0).is_err());
+        assert!(session_history_limit(-5).is_err());
+        assert_eq!(session_history_limit(1).unwrap(), 1);
+        assert_eq!(session_history_limit(1_000_000).unwrap(), MAX_HISTORY_LIMIT);
+    }
+
+    #[test]
+    fn session_limit_overrides_without_touching_config() {
+        let config = Config::default();
+        let configured = config.max_history_items;
+
+        assert_eq!(effective_history_limit(configured, None), configured);
+        assert_eq!(effective_history_limit(configured, Some(10)), 10);
+        assert_eq!(effective_history_limit(configured, Some(0)), configured);
+        assert_eq!(effective_history_limit(configured, Some(-1)), configured);
+
+        // 会话上限只参与计算，配置本身保持不变
+        assert_eq!(config.max_history_items, configured);
+        let saved = serde_json::to_value(&config).unwrap();
+        assert_eq!(saved["max_history_items"], configured);
+    }
+
+    #[test]
+    fn reconcile_autostart_prefers_system_state() {
+        assert_eq!(reconcile_autostart(true, true), None);
+        assert_eq!(reconcile_autostart(false, false), None);
+        assert_eq!(reconcile_autostart(true, false), Some(false));
+        assert_eq!(reconcile_autostart(false, true), Some(true));
+    }
+
+    #[test]
+    fn write_atomically_replaces_file_and_cleans_up() {
+        let dir = std::env::temp_dir().join(format!("cat-clipboard-config-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+
+        let path = dir.join("config.json");
+        fs::write(&path, "old").unwrap();
+        write_atomically(&path, "new").unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), "new");
+        assert!(!dir.join("config.json.tmp").exists());
+
+        // 目标是非空目录时重命名失败，临时文件也要被删除
+        let blocked = dir.join("blocked");
+        fs::create_dir_all(blocked.join("child")).unwrap();
+        assert!(write_atomically(&blocked, "content").is_err());
+        assert!(!dir.join("blocked.tmp").exists());
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn merged_auto_tag_rules_skip_existing() {
+        let rule = |pattern: &str, tag: &str| AutoTagRule {
+            pattern: pattern.into(),
+            tag: tag.into(),
+        };
+        let mut config = Config {
+            auto_tag_rules: vec![rule("^https?://", "link")],
+            ..Config::default()
+        };
+        let exported = vec![rule("^https?://", "link"), rule("TODO", "todo")];
+
+        assert_eq!(config.merge_auto_tag_rules(&exported), 1);
+        assert_eq!(config.auto_tag_rules, exported);
+        assert_eq!(config.merge_auto_tag_rules(&exported), 0);
+    }
+
+    #[test]
+    fn sanitize_drops_invalid_filter_presets() {
+        let preset = |key: &str, filter: &str| FilterPreset {
+            key: key.into(),
+            filter: filter.into(),
+        };
+        let mut config = Config {
+            filter_presets: vec![
+                preset(" 1 ", " favorites "),
+                preset("1", "all"),
+                preset("2", "type:video"),
+                preset("", "all"),
+                preset("3", "tag:work"),
+            ],
+            ..Config::default()
+        };
+        config.sanitize();
+
+        assert_eq!(
+            config.filter_presets,
+            vec![preset("1", "favorites"), preset("3", "tag:work")]
+        );
+        assert_eq!(config.filter_preset(" 3").unwrap().filter, "tag:work");
+        assert!(config.filter_preset("2").is_none());
+    }
+
+    #[test]
+    fn adjusted_fields_lists_sanitized_values() {
+        assert!(Config::default().adjusted_fields().unwrap().is_empty());
+
+        let config = Config {
+            max_history_items: 0,
+            image_format_priority: "gif".to_string(),
+            capture_allowlist: vec!["text".to_string(), "bogus".to_string()],
+            ..Config::default()
+        };
+
+        assert_eq!(
+            config.adjusted_fields().unwrap(),
+            vec![
+                "capture_allowlist",
+                "image_format_priority",
+                "max_history_items"
+            ]
+        );
+    }
+
+    #[test]
+    fn exit_clear_action_follows_config() {
+        let mut config = Config {
+            clear_on_exit_mode: "all".to_string(),
+            ..Config::default()
+        };
+        assert_eq!(config.exit_clear_action(), None);
+
+        config.clear_on_exit = true;
+        assert_eq!(config.exit_clear_action(), Some(ExitClearAction::All));
+
+        config.clear_on_exit_mode = "non_favorites".to_string();
+        assert_eq!(
+            config.exit_clear_action(),
+            Some(ExitClearAction::NonFavorites)
+        );
+
+        // 无法识别的模式按较保守的“仅非收藏”处理
+        config.clear_on_exit_mode = "everything".to_string();
+        assert_eq!(
+            config.exit_clear_action(),
+            Some(ExitClearAction::NonFavorites)
+        );
+    }
+
+    #[test]
+    fn window_actions_map_from_config() {
+        let with = |close: &str, minimize: &str| Config {
+            window_close_action: close.to_string(),
+            window_minimize_action: minimize.to_string(),
+            ..Config::default()
+        };
+
+        assert_eq!(Config::default().close_action(), WindowAction::Hide);
+        assert_eq!(Config::default().minimize_action(), WindowAction::Minimize);
+
+        assert_eq!(with("quit", "hide").close_action(), WindowAction::Quit);
+        assert_eq!(
+            with(" minimize ", "hide").close_action(),
+            WindowAction::Minimize
+        );
+        assert_eq!(with("hide", "hide").minimize_action(), WindowAction::Hide);
+
+        // 无效取值回退到默认；最小化时不允许退出
+        assert_eq!(with("explode", "quit").close_action(), WindowAction::Hide);
+        assert_eq!(
+            with("hide", "quit").minimize_action(),
+            WindowAction::Minimize
+        );
+    }
+}
@@ -1,9 +1,18 @@
-use anyhow::Result;
-use chrono::{DateTime, Utc};
-use rusqlite::{params, Connection, OptionalExtension};
+use anyhow::{anyhow, Result};
+use chrono::{DateTime, Duration, Utc};
+use regex::RegexBuilder;
+use rusqlite::{backup::Progress, params, Connection, OpenFlags, OptionalExtension, MAIN_DB};
 use serde::{Deserialize, Serialize};
-use std::path::PathBuf;
-use std::sync::{Arc, Mutex};
+use sha2::{Digest, Sha256};
+use std::path::{Path, PathBuf};
+use std::sync::{
+    atomic::{AtomicBool, Ordering},
+    Arc, Mutex,
+};
+use unicode_normalization::{char::is_combining_mark, UnicodeNormalization};
+
+use crate::clipboard::build_text_preview;
+use crate::import::ImportedEntry;
 
 /// 剪切板历史记录项
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -15,11 +24,222 @@ pub struct ClipboardItem {
     pub is_favorite: bool,
     pub tags: Vec<String>,
     pub created_at: String,
+    pub source_format: Option<String>, // 产生该记录的剪切板格式名，手动添加时为空
+    pub thumbnail: Option<String>,     // 图片记录的小尺寸 PNG 缩略图（base64）
+    pub source_title: Option<String>,  // 复制时前台窗口的标题，无法获取时为空
+}
+
+/// 不含完整内容的轻量记录，附带内容字节数
+#[derive(Debug, Clone, Serialize)]
+pub struct ItemSummary {
+    pub id: i64,
+    pub content_type: String,
+    pub preview: String,
+    pub is_favorite: bool,
+    pub created_at: String,
+    pub byte_size: i64,
+}
+
+/// 带 id 的标签，便于前端直接按 id 重命名或删除
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct TagRef {
+    pub id: i64,
+    pub name: String,
+}
+
+/// 附带完整标签对象的记录；`tags` 仍为名称列表以保持兼容
+#[derive(Debug, Clone, Serialize)]
+pub struct ItemWithTagIds {
+    #[serde(flatten)]
+    pub item: ClipboardItem,
+    pub tag_refs: Vec<TagRef>,
+}
+
+/// 回收站中的记录
+#[derive(Debug, Clone, Serialize)]
+pub struct TrashedItem {
+    #[serde(flatten)]
+    pub item: ClipboardItem,
+    pub deleted_at: String,
+}
+
+/// 操作审计日志条目
+#[derive(Debug, Clone, Serialize)]
+pub struct AuditEntry {
+    pub id: i64,
+    pub created_at: String,
+    /// "add", "delete", "touch", "edit", "favorite", "unfavorite", "tag", "untag", "delete_tag",
+    /// "normalize_tags", "repair_tags", "clear", "prune_images", "restore"
+    pub op: String,
+    pub item_id: Option<i64>,
+    pub detail: Option<String>,
+}
+
+/// 后台维护使用的保留策略
+#[derive(Debug, Clone, Copy)]
+pub struct MaintenanceOptions {
+    pub retention_days: i64,
+    pub trash_retention_minutes: i64,
+    pub audit_retention_days: i64,
+}
+
+/// 清理旧图片的结果
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ImagePruneReport {
+    pub removed: usize,
+    pub bytes_reclaimed: i64, // 被删除图片内容与缩略图的字节数
+}
+
+/// 合并大小写不同的重复标签时，保留下来的名称
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TagCasePolicy {
+    /// 合并后的标签改为小写
+    Lower,
+    /// 保留最早创建的那个写法
+    PreserveFirst,
+}
+
+impl TagCasePolicy {
+    pub fn parse(name: &str) -> Result<Self> {
+        match name.trim() {
+            "lower" => Ok(Self::Lower),
+            "preserve-first" => Ok(Self::PreserveFirst),
+            other => Err(anyhow!("Unsupported tag case policy: {other}")),
+        }
+    }
+}
+
+/// 快速筛选对应的查询
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum HistoryFilter {
+    All,
+    Favorites,
+    Untagged,
+    /// 指定内容类型："text"、"image"、"file"
+    ContentType(String),
+    Tag(String),
+    /// 已收藏且带有该标签
+    FavoriteTag(String),
+}
+
+impl HistoryFilter {
+    /// 解析筛选描述：`all`、`favorites`、`untagged`、`type:<类型>`、`tag:<标签>`、`favorite-tag:<标签>`
+    pub fn parse(spec: &str) -> Result<Self> {
+        let spec = spec.trim();
+        let filter = match spec.split_once(':') {
+            None => match spec {
+                "all" => Self::All,
+                "favorites" => Self::Favorites,
+                "untagged" => Self::Untagged,
+                _ => return Err(anyhow!("Unsupported filter: {spec}")),
+            },
+            Some((kind, value)) => {
+                let value = value.trim();
+                if value.is_empty() {
+                    return Err(anyhow!("Filter {kind} requires a value"));
+                }
+                match kind.trim() {
+                    "type" if matches!(value, "text" | "image" | "file") => {
+                        Self::ContentType(value.to_string())
+                    }
+                    "tag" => Self::Tag(value.to_string()),
+                    "favorite-tag" => Self::FavoriteTag(value.to_string()),
+                    _ => return Err(anyhow!("Unsupported filter: {spec}")),
+                }
+            }
+        };
+        Ok(filter)
+    }
+}
+
+/// 退出应用时的清空范围
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExitClearAction {
+    /// 永久删除非收藏记录
+    NonFavorites,
+    /// 永久删除全部记录（含收藏）
+    All,
+}
+
+impl ExitClearAction {
+    pub fn parse(name: &str) -> Result<Self> {
+        match name.trim() {
+            "non_favorites" => Ok(Self::NonFavorites),
+            "all" => Ok(Self::All),
+            other => Err(anyhow!("Unsupported exit clear mode: {other}")),
+        }
+    }
+}
+
+/// 综合排序（新近程度 + 使用次数 + 收藏）的权重
+#[derive(Debug, Clone, Copy)]
+pub struct RankingWeights {
+    pub recency: f64,
+    pub usage: f64,
+    pub favorite: f64,
+}
+
+/// 一批后台补齐的进度
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct BackfillProgress {
+    pub processed: usize,
+    /// 尚待补齐的记录数
+    pub remaining: i64,
+    pub done: bool,
+}
+
+/// 一次后台维护的执行结果
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct MaintenanceSummary {
+    pub pruned: usize,
+    pub trash_purged: usize,
+    pub audit_trimmed: usize,
+    pub fts_optimized: bool,
+    pub vacuumed: bool,
 }
 
+/// 正则搜索最多扫描的记录数（从最新开始）
+const REGEX_SCAN_LIMIT: i64 = 5_000;
+/// 正则搜索的总耗时上限，超时后返回已找到的结果
+const REGEX_SCAN_BUDGET: std::time::Duration = std::time::Duration::from_secs(2);
+/// 编译后正则的大小上限，避免超大模式占用过多内存
+const REGEX_SIZE_LIMIT: usize = 1 << 20;
+
+/// 元数据键：旧记录是否已补齐哈希、时间戳秒数与搜索文本
+const META_BACKFILL_DONE: &str = "backfill_done";
+/// 元数据键：后台补齐已处理到的记录 id
+const META_BACKFILL_CURSOR: &str = "backfill_cursor";
+/// 元数据键：修正触发器后是否已重建过全文索引
+const META_FTS_REBUILT: &str = "fts_rebuilt";
+/// 元数据键：最近一次后台维护的时间（Unix 秒）
+const META_MAINTENANCE_LAST_RUN: &str = "maintenance_last_run";
+/// 记录时间的秒数：后台补齐前 `created_at_epoch` 可能为 NULL，此时从 `created_at` 解析
+const EPOCH_EXPR: &str = "IFNULL(created_at_epoch, CAST(strftime('%s', created_at) AS INTEGER))";
+/// 与 ?1（内容哈希）和 ?2（内容）相同的记录；哈希尚未补齐时直接比较内容
+const SAME_CONTENT_FILTER: &str = "(content_hash = ?1 OR (content_hash IS NULL AND content = ?2))";
+/// 需要后台补齐的记录
+const BACKFILL_PENDING_FILTER: &str =
+    "content_hash IS NULL OR created_at_epoch IS NULL OR search_text IS NULL";
+
+/// 空闲页占比超过该值时执行 VACUUM
+const VACUUM_FREELIST_RATIO: f64 = 0.2;
+
 /// 数据库管理器
 pub struct Database {
     conn: Arc<Mutex<Connection>>,
+    audit_enabled: AtomicBool,
+    /// 允许写入历史的内容类型，None 表示不限制
+    capture_allowlist: Mutex<Option<Vec<String>>>,
+}
+
+/// 批量导入外部记录的写入结果
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ImportedCounts {
+    pub inserted: usize,
+    /// 时间与内容都已存在而跳过的条目
+    pub duplicates: usize,
+    /// 类型不在记录范围内而跳过的条目
+    pub disallowed: usize,
 }
 
 fn build_like_pattern(input: &str) -> Option<String> {
@@ -45,13 +265,169 @@ fn build_like_pattern(input: &str) -> Option<String> {
     }
 }
 
+const ITEM_COLUMNS: &str =
+    "h.id, h.content_type, h.content, h.preview, h.is_favorite, h.created_at, h.source_format, \
+     h.thumbnail, h.source_title";
+
+fn item_from_row(row: &rusqlite::Row<'_>) -> rusqlite::Result<ClipboardItem> {
+    Ok(ClipboardItem {
+        id: row.get(0)?,
+        content_type: row.get(1)?,
+        content: row.get(2)?,
+        preview: row.get(3)?,
+        is_favorite: row.get::<_, i64>(4)? != 0,
+        tags: Vec::new(),
+        created_at: row.get(5)?,
+        source_format: row.get(6)?,
+        thumbnail: row.get(7)?,
+        source_title: row.get(8)?,
+    })
+}
+
+/// 在历史表与回收站之间搬移记录时复制的列
+const TRASH_COLUMNS: &str = "id, content_type, content, preview, is_favorite, created_at, \
+     source_format, content_hash, created_at_epoch, thumbnail, search_text, copy_count, \
+     source_title";
+
+/// 筛选悬空的 item_tags 行：对应的记录或标签已不存在
+const ORPHAN_ASSOCIATION_FILTER: &str =
+    "item_id NOT IN (SELECT id FROM clipboard_history) OR tag_id NOT IN (SELECT id FROM tags)";
+
+/// 计算内容哈希（SHA-256 十六进制），用于去重与快速比对
+pub(crate) fn content_hash(content: &str) -> String {
+    Sha256::digest(content.as_bytes())
+        .iter()
+        .map(|byte| format!("{byte:02x}"))
+        .collect()
+}
+
+/// 将文本折叠为便于模糊匹配的形式：NFKD 分解（全角转半角）、去掉变音符号并转小写
+pub(crate) fn fold_search_text(text: &str) -> String {
+    text.nfkd()
+        .filter(|ch| !is_combining_mark(*ch))
+        .flat_map(char::to_lowercase)
+        .collect()
+}
+
+/// 计算记录的搜索文本（附带来源窗口标题）；图片内容为 base64，只折叠预览
+fn search_text_for(
+    content_type: &str,
+    content: &str,
+    preview: &str,
+    source_title: Option<&str>,
+) -> String {
+    let title = source_title.unwrap_or_default();
+    if content_type == "image" {
+        fold_search_text(&format!("{preview}\n{title}"))
+    } else {
+        fold_search_text(&format!("{content}\n{preview}\n{title}"))
+    }
+}
+
+/// 规范化文件路径以便比较：统一使用 `/` 分隔、去掉结尾分隔符；
+/// `case_insensitive` 时同时转为小写（Windows 路径不区分大小写）
+pub(crate) fn normalize_path_for_match(path: &str, case_insensitive: bool) -> String {
+    let unified = path.trim().replace('\\', "/");
+    let trimmed = unified.trim_end_matches('/');
+    let normalized = if trimmed.is_empty() {
+        unified.as_str()
+    } else {
+        trimmed
+    };
+    if case_insensitive {
+        normalized.to_lowercase()
+    } else {
+        normalized.to_string()
+    }
+}
+
+/// 将 RFC3339 时间转换为 Unix 秒
+fn rfc3339_epoch(timestamp: &str) -> Option<i64> {
+    DateTime::parse_from_rfc3339(timestamp)
+        .ok()
+        .map(|parsed| parsed.timestamp())
+}
+
+/// 为旧版本数据库补充新增的列
+fn ensure_column(conn: &Connection, table: &str, column: &str, definition: &str) -> Result<()> {
+    let mut stmt = conn.prepare(&format!("PRAGMA table_info({table})"))?;
+    let exists = stmt
+        .query_map([], |row| row.get::<_, String>(1))?
+        .collect::<Result<Vec<_>, _>>()?
+        .iter()
+        .any(|name| name == column);
+
+    if !exists {
+        conn.execute(
+            &format!("ALTER TABLE {table} ADD COLUMN {column} {definition}"),
+            [],
+        )?;
+    }
+    Ok(())
+}
+
+/// 检查文件是否为本应用可用的数据库备份：完整性正常且包含历史与标签表的必需列
+fn validate_backup(path: &Path) -> Result<()> {
+    const REQUIRED: [(&str, &[&str]); 3] = [
+        (
+            "clipboard_history",
+            &[
+                "id",
+                "content_type",
+                "content",
+                "preview",
+                "is_favorite",
+                "created_at",
+            ],
+        ),
+        ("tags", &["id", "name"]),
+        ("item_tags", &["item_id", "tag_id"]),
+    ];
+
+    let conn = Connection::open_with_flags(path, OpenFlags::SQLITE_OPEN_READ_ONLY)?;
+
+    let integrity: String = conn.query_row("PRAGMA quick_check", [], |row| row.get(0))?;
+    if integrity != "ok" {
+        return Err(anyhow!("Backup failed integrity check: {integrity}"));
+    }
+
+    for (table, columns) in REQUIRED {
+        let mut stmt = conn.prepare(&format!("PRAGMA table_info({table})"))?;
+        let existing = stmt
+            .query_map([], |row| row.get::<_, String>(1))?
+            .collect::<Result<Vec<_>, _>>()?;
+        if existing.is_empty() {
+            return Err(anyhow!("Backup is missing table {table}"));
+        }
+        let missing = columns
+            .iter()
+            .find(|column| !existing.iter().any(|name| name == *column));
+        if let Some(missing) = missing {
+            return Err(anyhow!("Backup table {table} is missing column {missing}"));
+        }
+    }
+
+    Ok(())
+}
+
 impl Database {
     /// 初始化数据库
     pub fn new(db_path: PathBuf) -> Result<Self> {
         let conn = Connection::open(db_path)?;
 
+        Self::init_schema(&conn)?;
+
+        Ok(Database {
+            conn: Arc::new(Mutex::new(conn)),
+            audit_enabled: AtomicBool::new(false),
+            capture_allowlist: Mutex::new(None),
+        })
+    }
+
+    /// 创建缺失的表、索引与触发器并执行列迁移（打开数据库与恢复备份后调用）
+    fn init_schema(conn: &Connection) -> Result<()> {
         conn.execute("PRAGMA foreign_keys = ON", [])?;
-        
+
         // 创建历史记录表
         conn.execute(
             "CREATE TABLE IF NOT EXISTS clipboard_history (
@@ -60,10 +436,33 @@ impl Database {
                 content TEXT NOT NULL,
                 preview TEXT NOT NULL,
                 is_favorite INTEGER DEFAULT 0,
-                created_at TEXT NOT NULL
+                created_at TEXT NOT NULL,
+                source_format TEXT,
+                content_hash TEXT,
+                created_at_epoch INTEGER,
+                thumbnail TEXT,
+                search_text TEXT,
+                copy_count INTEGER NOT NULL DEFAULT 0,
+                source_title TEXT
             )",
             [],
         )?;
+        ensure_column(conn, "clipboard_history", "source_format", "TEXT")?;
+        ensure_column(conn, "clipboard_history", "content_hash", "TEXT")?;
+        ensure_column(conn, "clipboard_history", "created_at_epoch", "INTEGER")?;
+        ensure_column(conn, "clipboard_history", "thumbnail", "TEXT")?;
+        ensure_column(conn, "clipboard_history", "search_text", "TEXT")?;
+        ensure_column(
+            conn,
+            "clipboard_history",
+            "copy_count",
+            "INTEGER NOT NULL DEFAULT 0",
+        )?;
+        ensure_column(conn, "clipboard_history", "source_title", "TEXT")?;
+        conn.execute(
+            "CREATE INDEX IF NOT EXISTS idx_history_content_hash ON clipboard_history(content_hash)",
+            [],
+        )?;
 
         // 创建标签表
         conn.execute(
@@ -86,6 +485,39 @@ impl Database {
             [],
         )?;
 
+        // 创建回收站表，保存被清空的记录以便在保留期内恢复
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS clipboard_trash (
+                id INTEGER PRIMARY KEY,
+                content_type TEXT NOT NULL,
+                content TEXT NOT NULL,
+                preview TEXT NOT NULL,
+                is_favorite INTEGER DEFAULT 0,
+                created_at TEXT NOT NULL,
+                source_format TEXT,
+                content_hash TEXT,
+                created_at_epoch INTEGER,
+                thumbnail TEXT,
+                search_text TEXT,
+                copy_count INTEGER NOT NULL DEFAULT 0,
+                source_title TEXT,
+                tags TEXT NOT NULL DEFAULT '[]',
+                deleted_at TEXT NOT NULL
+            )",
+            [],
+        )?;
+        ensure_column(conn, "clipboard_trash", "content_hash", "TEXT")?;
+        ensure_column(conn, "clipboard_trash", "created_at_epoch", "INTEGER")?;
+        ensure_column(conn, "clipboard_trash", "thumbnail", "TEXT")?;
+        ensure_column(conn, "clipboard_trash", "search_text", "TEXT")?;
+        ensure_column(
+            conn,
+            "clipboard_trash",
+            "copy_count",
+            "INTEGER NOT NULL DEFAULT 0",
+        )?;
+        ensure_column(conn, "clipboard_trash", "source_title", "TEXT")?;
+
         // 创建全文搜索虚拟表
         conn.execute(
             "CREATE VIRTUAL TABLE IF NOT EXISTS clipboard_fts USING fts5(
@@ -106,313 +538,856 @@ impl Database {
             [],
         )?;
 
+        // 外部内容表必须用 'delete' 命令按旧值删除词条，否则旧词仍留在索引中；
+        // 早期版本的删除与更新触发器直接改写索引表，这里重建为正确的写法
+        conn.execute_batch(
+            "DROP TRIGGER IF EXISTS clipboard_ad;
+             DROP TRIGGER IF EXISTS clipboard_au;
+             CREATE TRIGGER clipboard_ad AFTER DELETE ON clipboard_history BEGIN
+                INSERT INTO clipboard_fts(clipboard_fts, rowid, content, preview)
+                VALUES ('delete', old.id, old.content, old.preview);
+             END;
+             CREATE TRIGGER clipboard_au AFTER UPDATE OF content, preview ON clipboard_history
+             BEGIN
+                INSERT INTO clipboard_fts(clipboard_fts, rowid, content, preview)
+                VALUES ('delete', old.id, old.content, old.preview);
+                INSERT INTO clipboard_fts(rowid, content, preview)
+                VALUES (new.id, new.content, new.preview);
+             END;",
+        )?;
+
+        // 创建元数据表（后台补齐进度等）
         conn.execute(
-            "CREATE TRIGGER IF NOT EXISTS clipboard_ad AFTER DELETE ON clipboard_history BEGIN
-                DELETE FROM clipboard_fts WHERE rowid = old.id;
-            END",
+            "CREATE TABLE IF NOT EXISTS app_meta (
+                key TEXT PRIMARY KEY,
+                value TEXT NOT NULL
+            )",
             [],
         )?;
 
+        // 旧触发器可能已留下过期词条，升级后重建一次全文索引
+        if Self::get_meta(conn, META_FTS_REBUILT)?.is_none() {
+            conn.execute(
+                "INSERT INTO clipboard_fts(clipboard_fts) VALUES('rebuild')",
+                [],
+            )?;
+            Self::set_meta(conn, META_FTS_REBUILT, "1")?;
+        }
+
+        // 创建操作审计日志表
         conn.execute(
-            "CREATE TRIGGER IF NOT EXISTS clipboard_au AFTER UPDATE ON clipboard_history BEGIN
-                UPDATE clipboard_fts SET content = new.content, preview = new.preview 
-                WHERE rowid = new.id;
-            END",
+            "CREATE TABLE IF NOT EXISTS audit_log (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                created_at TEXT NOT NULL,
+                op TEXT NOT NULL,
+                item_id INTEGER,
+                detail TEXT
+            )",
             [],
         )?;
 
-        Ok(Database {
-            conn: Arc::new(Mutex::new(conn)),
-        })
+        Ok(())
     }
 
-    /// 清空所有数据
-    pub fn reset_all(&self) -> Result<()> {
-        let conn = self.conn.lock().unwrap();
-        let tx = conn.unchecked_transaction()?;
-
-        tx.execute("DELETE FROM item_tags", [])?;
-        tx.execute("DELETE FROM tags", [])?;
-        tx.execute("DELETE FROM clipboard_history", [])?;
-        tx.execute("DELETE FROM clipboard_fts", [])?;
-
-        tx.commit()?;
-        Ok(())
+    /// 读取元数据表中的值
+    fn get_meta(conn: &Connection, key: &str) -> Result<Option<String>> {
+        let value = conn
+            .query_row(
+                "SELECT value FROM app_meta WHERE key = ?1",
+                params![key],
+                |row| row.get(0),
+            )
+            .optional()?;
+        Ok(value)
     }
 
-    /// 添加剪切板记录
-    pub fn add_item(&self, content_type: &str, content: &str, preview: &str) -> Result<i64> {
-        let conn = self.conn.lock().unwrap();
-        let now: DateTime<Utc> = Utc::now();
-        
+    fn set_meta(conn: &Connection, key: &str, value: &str) -> Result<()> {
         conn.execute(
-            "INSERT INTO clipboard_history (content_type, content, preview, created_at) 
-             VALUES (?1, ?2, ?3, ?4)",
-            params![content_type, content, preview, now.to_rfc3339()],
+            "INSERT INTO app_meta (key, value) VALUES (?1, ?2)
+             ON CONFLICT(key) DO UPDATE SET value = excluded.value",
+            params![key, value],
         )?;
+        Ok(())
+    }
 
-        Ok(conn.last_insert_rowid())
+    /// 旧记录的哈希、时间戳秒数与搜索文本是否已全部补齐
+    pub fn is_backfill_done(&self) -> Result<bool> {
+        let conn = self.conn.lock().unwrap();
+        Ok(Self::get_meta(&conn, META_BACKFILL_DONE)?.as_deref() == Some("1"))
     }
 
-    /// 获取所有历史记录（带分页）
-    pub fn get_items(&self, limit: i64, offset: i64) -> Result<Vec<ClipboardItem>> {
+    /// 按 id 顺序为一批旧记录补齐缺失的哈希、时间戳秒数与搜索文本。
+    /// 进度游标保存在元数据表中，全部处理完后写入 `backfill_done` 标记
+    pub fn backfill_batch(&self, batch_size: usize) -> Result<BackfillProgress> {
+        let batch_size = batch_size.max(1);
         let conn = self.conn.lock().unwrap();
-        let mut stmt = conn.prepare(
-            "SELECT id, content_type, content, preview, is_favorite, created_at 
-             FROM clipboard_history 
-             ORDER BY created_at DESC 
-             LIMIT ?1 OFFSET ?2",
-        )?;
+        let cursor: i64 = Self::get_meta(&conn, META_BACKFILL_CURSOR)?
+            .and_then(|value| value.parse().ok())
+            .unwrap_or(0);
 
-        let items = stmt
-            .query_map(params![limit, offset], |row| {
-                let item_id: i64 = row.get(0)?;
-                Ok(ClipboardItem {
-                    id: item_id,
-                    content_type: row.get(1)?,
-                    content: row.get(2)?,
-                    preview: row.get(3)?,
-                    is_favorite: row.get::<_, i64>(4)? != 0,
-                    tags: Vec::new(), // 稍后填充
-                    created_at: row.get(5)?,
-                })
-            })?
-            .collect::<Result<Vec<_>, _>>()?;
+        let pending = {
+            let mut stmt = conn.prepare(&format!(
+                "SELECT id, content_type, content, preview, created_at, source_title,
+                        content_hash IS NULL, created_at_epoch IS NULL, search_text IS NULL
+                 FROM clipboard_history
+                 WHERE id > ?1 AND ({BACKFILL_PENDING_FILTER})
+                 ORDER BY id
+                 LIMIT ?2"
+            ))?;
+            let rows = stmt
+                .query_map(params![cursor, batch_size as i64], |row| {
+                    Ok((
+                        row.get::<_, i64>(0)?,
+                        row.get::<_, String>(1)?,
+                        row.get::<_, String>(2)?,
+                        row.get::<_, String>(3)?,
+                        row.get::<_, String>(4)?,
+                        row.get::<_, Option<String>>(5)?,
+                        [
+                            row.get::<_, bool>(6)?,
+                            row.get::<_, bool>(7)?,
+                            row.get::<_, bool>(8)?,
+                        ],
+                    ))
+                })?
+                .collect::<Result<Vec<_>, _>>()?;
+            rows
+        };
 
-        // 为每个项目获取标签
-        let mut items_with_tags = Vec::new();
-        for mut item in items {
-            item.tags = self.get_item_tags_internal(&conn, item.id)?;
-            items_with_tags.push(item);
+        let tx = conn.unchecked_transaction()?;
+        let mut last_id = cursor;
+        for (id, content_type, content, preview, created_at, source_title, missing) in &pending {
+            let [missing_hash, missing_epoch, missing_search] = *missing;
+            tx.execute(
+                "UPDATE clipboard_history
+                 SET content_hash = COALESCE(content_hash, ?1),
+                     created_at_epoch = COALESCE(created_at_epoch, ?2),
+                     search_text = COALESCE(search_text, ?3)
+                 WHERE id = ?4",
+                params![
+                    missing_hash.then(|| content_hash(content)),
+                    missing_epoch.then(|| rfc3339_epoch(created_at)).flatten(),
+                    missing_search.then(|| {
+                        search_text_for(content_type, content, preview, source_title.as_deref())
+                    }),
+                    id,
+                ],
+            )?;
+            last_id = *id;
         }
 
-        Ok(items_with_tags)
-    }
-
-    /// 搜索历史记录
-    pub fn search_items(&self, query: &str, limit: i64) -> Result<Vec<ClipboardItem>> {
-        let trimmed = query.trim();
-        if trimmed.is_empty() {
-            return self.get_items(limit, 0);
+        let done = pending.len() < batch_size;
+        Self::set_meta(&tx, META_BACKFILL_CURSOR, &last_id.to_string())?;
+        if done {
+            Self::set_meta(&tx, META_BACKFILL_DONE, "1")?;
         }
+        tx.commit()?;
 
-        let conn = self.conn.lock().unwrap();
-        let like_pattern = match build_like_pattern(trimmed) {
-            Some(pattern) => pattern,
-            None => return Ok(Vec::new()),
+        let remaining: i64 = if done {
+            0
+        } else {
+            conn.query_row(
+                &format!(
+                    "SELECT COUNT(*) FROM clipboard_history
+                     WHERE id > ?1 AND ({BACKFILL_PENDING_FILTER})"
+                ),
+                params![last_id],
+                |row| row.get(0),
+            )?
         };
-        let like_param = like_pattern.to_lowercase();
 
-        let mut stmt = conn.prepare(
-            "SELECT DISTINCT h.id, h.content_type, h.content, h.preview, h.is_favorite, h.created_at
-             FROM clipboard_history h
-             LEFT JOIN item_tags it ON h.id = it.item_id
-             LEFT JOIN tags t ON it.tag_id = t.id
-             WHERE LOWER(h.content) LIKE ?1 ESCAPE '\\'
-                OR LOWER(h.preview) LIKE ?1 ESCAPE '\\'
-                OR LOWER(IFNULL(t.name, '')) LIKE ?1 ESCAPE '\\'
-             ORDER BY h.is_favorite DESC, h.created_at DESC
-             LIMIT ?2",
-        )?;
+        Ok(BackfillProgress {
+            processed: pending.len(),
+            remaining,
+            done,
+        })
+    }
 
-        let items = stmt
-            .query_map(params![like_param, limit], |row| {
-                let item_id: i64 = row.get(0)?;
-                Ok(ClipboardItem {
-                    id: item_id,
-                    content_type: row.get(1)?,
-                    content: row.get(2)?,
-                    preview: row.get(3)?,
-                    is_favorite: row.get::<_, i64>(4)? != 0,
-                    tags: Vec::new(),
-                    created_at: row.get(5)?,
-                })
-            })?
-            .collect::<Result<Vec<_>, _>>()?;
+    /// 计算并写入搜索文本；`all` 为 false 时只处理尚未计算过的记录，返回处理的数量
+    fn fill_search_text(conn: &Connection, all: bool) -> Result<usize> {
+        let pending = {
+            let filter = if all { "" } else { "WHERE search_text IS NULL" };
+            let mut stmt = conn.prepare(&format!(
+                "SELECT id, content_type, content, preview, source_title
+                 FROM clipboard_history {filter}"
+            ))?;
+            let rows = stmt
+                .query_map([], |row| {
+                    Ok((
+                        row.get::<_, i64>(0)?,
+                        row.get::<_, String>(1)?,
+                        row.get::<_, String>(2)?,
+                        row.get::<_, String>(3)?,
+                        row.get::<_, Option<String>>(4)?,
+                    ))
+                })?
+                .collect::<Result<Vec<_>, _>>()?;
+            rows
+        };
 
-        let mut items_with_tags = Vec::with_capacity(items.len());
-        for mut item in items {
-            item.tags = self.get_item_tags_internal(&conn, item.id)?;
-            items_with_tags.push(item);
+        if pending.is_empty() {
+            return Ok(0);
         }
 
-        Ok(items_with_tags)
+        let tx = conn.unchecked_transaction()?;
+        for (id, content_type, content, preview, source_title) in &pending {
+            tx.execute(
+                "UPDATE clipboard_history SET search_text = ?1 WHERE id = ?2",
+                params![
+                    search_text_for(content_type, content, preview, source_title.as_deref()),
+                    id
+                ],
+            )?;
+        }
+        tx.commit()?;
+        Ok(pending.len())
     }
 
-    /// 切换收藏状态
-    pub fn toggle_favorite(&self, id: i64) -> Result<bool> {
+    /// 重新计算全部记录的搜索文本，返回处理的数量
+    pub fn rebuild_search_text(&self) -> Result<usize> {
         let conn = self.conn.lock().unwrap();
-        let is_favorite: i64 = conn
+        Self::fill_search_text(&conn, true)
+    }
+
+    /// 按内容查找最新的一条相同记录（通过内容哈希索引）
+    pub fn find_by_content(&self, content_type: &str, content: &str) -> Result<Option<i64>> {
+        let conn = self.conn.lock().unwrap();
+        let id = conn
             .query_row(
-                "SELECT is_favorite FROM clipboard_history WHERE id = ?1",
-                params![id],
+                &format!(
+                    "SELECT id FROM clipboard_history
+                     WHERE {SAME_CONTENT_FILTER} AND content_type = ?3
+                     ORDER BY created_at DESC
+                     LIMIT 1"
+                ),
+                params![content_hash(content), content, content_type],
                 |row| row.get(0),
             )
-            .optional()?
-            .unwrap_or(0);
+            .optional()?;
+        Ok(id)
+    }
 
-        let new_state = if is_favorite == 0 { 1 } else { 0 };
-        
-        conn.execute(
-            "UPDATE clipboard_history SET is_favorite = ?1 WHERE id = ?2",
-            params![new_state, id],
-        )?;
+    /// 查找时间戳与内容都相同的记录（内部方法，供导入去重使用）
+    fn find_by_timestamp_and_content(
+        conn: &Connection,
+        epoch: i64,
+        hash: &str,
+        content: &str,
+    ) -> Result<Option<i64>> {
+        let id = conn
+            .query_row(
+                &format!(
+                    "SELECT id FROM clipboard_history
+                     WHERE {SAME_CONTENT_FILTER} AND {EPOCH_EXPR} = ?3
+                     LIMIT 1"
+                ),
+                params![hash, content, epoch],
+                |row| row.get(0),
+            )
+            .optional()?;
+        Ok(id)
+    }
 
-        Ok(new_state != 0)
+    /// 开启或关闭操作审计日志
+    pub fn set_audit_enabled(&self, enabled: bool) {
+        self.audit_enabled.store(enabled, Ordering::Relaxed);
     }
 
-    /// 删除记录
-    pub fn delete_item(&self, id: i64) -> Result<()> {
-        let conn = self.conn.lock().unwrap();
-        conn.execute("DELETE FROM clipboard_history WHERE id = ?1", params![id])?;
-        Ok(())
+    /// 设置允许写入历史的内容类型（配置中的 `capture_allowlist`），
+    /// 捕获、手动添加与各种导入都按它过滤
+    pub fn set_capture_allowlist(&self, allowlist: &[String]) {
+        *self.capture_allowlist.lock().unwrap() = Some(allowlist.to_vec());
     }
 
-    /// 清空所有非收藏的历史记录
-    pub fn clear_non_favorites(&self) -> Result<()> {
-        let conn = self.conn.lock().unwrap();
-        conn.execute("DELETE FROM clipboard_history WHERE is_favorite = 0", [])?;
-        Ok(())
+    fn allows_type(&self, content_type: &str) -> bool {
+        self.capture_allowlist
+            .lock()
+            .unwrap()
+            .as_ref()
+            .is_none_or(|allowlist| allowlist.iter().any(|allowed| allowed == content_type))
     }
 
-    /// 维护历史记录数量上限
-    pub fn maintain_limit(&self, max_items: i64) -> Result<()> {
-        let conn = self.conn.lock().unwrap();
-        if max_items <= 0 {
-            conn.execute("DELETE FROM clipboard_history", [])?;
+    fn ensure_allowed(&self, content_type: &str) -> Result<()> {
+        if self.allows_type(content_type) {
+            Ok(())
+        } else {
+            Err(anyhow!(
+                "Content type {content_type} is not in the capture allowlist"
+            ))
+        }
+    }
+
+    /// 写入一条审计日志（未开启时不做任何事）
+    fn record_audit(
+        &self,
+        conn: &Connection,
+        op: &str,
+        item_id: Option<i64>,
+        detail: Option<&str>,
+    ) -> Result<()> {
+        if !self.audit_enabled.load(Ordering::Relaxed) {
             return Ok(());
         }
 
-        let total: i64 = conn.query_row(
-            "SELECT COUNT(*) FROM clipboard_history",
-            [],
-            |row| row.get(0),
+        conn.execute(
+            "INSERT INTO audit_log (created_at, op, item_id, detail) VALUES (?1, ?2, ?3, ?4)",
+            params![Utc::now().to_rfc3339(), op, item_id, detail],
         )?;
+        Ok(())
+    }
 
-        if total <= max_items {
-            return Ok(());
+    /// 分页获取审计日志，最新的在前
+    pub fn get_audit_log(&self, limit: i64, offset: i64) -> Result<Vec<AuditEntry>> {
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = conn.prepare(
+            "SELECT id, created_at, op, item_id, detail FROM audit_log
+             ORDER BY id DESC
+             LIMIT ?1 OFFSET ?2",
+        )?;
+
+        let entries = stmt
+            .query_map(params![limit, offset], |row| {
+                Ok(AuditEntry {
+                    id: row.get(0)?,
+                    created_at: row.get(1)?,
+                    op: row.get(2)?,
+                    item_id: row.get(3)?,
+                    detail: row.get(4)?,
+                })
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(entries)
+    }
+
+    /// 删除早于指定天数的审计日志（内部方法，用于已有连接）
+    fn trim_audit_log_internal(conn: &Connection, days: i64) -> Result<usize> {
+        if days <= 0 {
+            return Ok(0);
         }
 
-        let to_remove = total - max_items;
+        let cutoff = (Utc::now() - Duration::days(days)).to_rfc3339();
+        let removed = conn.execute(
+            "DELETE FROM audit_log WHERE created_at < ?1",
+            params![cutoff],
+        )?;
+        Ok(removed)
+    }
 
-        let removed_non_favorites = conn.execute(
-            "DELETE FROM clipboard_history WHERE id IN (
-                 SELECT id FROM clipboard_history
-                 WHERE is_favorite = 0
-                 ORDER BY created_at ASC, id ASC
-                 LIMIT ?1
-             )",
-            params![to_remove],
-        )? as i64;
+    /// 统计指向已不存在的记录或标签的 item_tags 行
+    pub fn count_orphan_associations(&self) -> Result<i64> {
+        let conn = self.conn.lock().unwrap();
+        let count = conn.query_row(
+            &format!("SELECT COUNT(*) FROM item_tags WHERE {ORPHAN_ASSOCIATION_FILTER}"),
+            [],
+            |row| row.get(0),
+        )?;
+        Ok(count)
+    }
 
-        let remaining = to_remove.saturating_sub(removed_non_favorites);
+    /// 删除悬空的 item_tags 行（外部修改数据库且未开启外键时可能出现），返回删除数量，
+    /// 并重新开启外键约束
+    pub fn repair_orphan_associations(&self) -> Result<usize> {
+        let conn = self.conn.lock().unwrap();
+        let removed = conn.execute(
+            &format!("DELETE FROM item_tags WHERE {ORPHAN_ASSOCIATION_FILTER}"),
+            [],
+        )?;
+        conn.execute("PRAGMA foreign_keys = ON", [])?;
 
-        if remaining > 0 {
-            conn.execute(
-                "DELETE FROM clipboard_history WHERE id IN (
-                     SELECT id FROM clipboard_history
-                     ORDER BY created_at ASC, id ASC
-                     LIMIT ?1
-                 )",
-                params![remaining],
-            )?;
+        if removed > 0 {
+            self.record_audit(&conn, "repair_tags", None, Some(&removed.to_string()))?;
         }
+        Ok(removed)
+    }
+
+    /// 清空所有数据
+    pub fn reset_all(&self) -> Result<()> {
+        let conn = self.conn.lock().unwrap();
+        let tx = conn.unchecked_transaction()?;
+
+        tx.execute("DELETE FROM item_tags", [])?;
+        tx.execute("DELETE FROM tags", [])?;
+        tx.execute("DELETE FROM clipboard_history", [])?;
+        tx.execute("DELETE FROM clipboard_fts", [])?;
+        tx.execute("DELETE FROM clipboard_trash", [])?;
+        tx.execute("DELETE FROM audit_log", [])?;
+
+        tx.commit()?;
         Ok(())
     }
 
-    /// 添加标签
-    pub fn add_tag(&self, name: &str) -> Result<i64> {
+    /// 使用 SQLite 在线备份 API 将当前数据库完整复制到 `dest`，应用运行期间也能得到一致的快照
+    pub fn backup_to(&self, dest: &Path) -> Result<()> {
         let conn = self.conn.lock().unwrap();
-        conn.execute("INSERT OR IGNORE INTO tags (name) VALUES (?1)", params![name])?;
-        
-        let tag_id: i64 = conn.query_row(
-            "SELECT id FROM tags WHERE name = ?1",
-            params![name],
-            |row| row.get(0),
-        )?;
-        
-        Ok(tag_id)
+        conn.backup(MAIN_DB, dest, None)?;
+        Ok(())
     }
 
-    /// 为项目添加标签
-    pub fn add_item_tag(&self, item_id: i64, tag_name: &str) -> Result<()> {
-        let tag_id = self.add_tag(tag_name)?;
+    /// 校验备份文件后用其内容替换当前数据库，并对恢复后的库补齐表结构与迁移
+    pub fn restore_from(&self, src: &Path) -> Result<()> {
+        validate_backup(src)?;
+
+        let mut conn = self.conn.lock().unwrap();
+        conn.restore(MAIN_DB, src, None::<fn(Progress)>)?;
+        Self::init_schema(&conn)?;
+        self.record_audit(&conn, "restore", None, src.to_str())?;
+        Ok(())
+    }
+
+    /// 添加剪切板记录
+    pub fn add_item(
+        &self,
+        content_type: &str,
+        content: &str,
+        preview: &str,
+        source_format: Option<&str>,
+        source_title: Option<&str>,
+        thumbnail: Option<&str>,
+    ) -> Result<i64> {
+        self.ensure_allowed(content_type)?;
+
         let conn = self.conn.lock().unwrap();
+        let now: DateTime<Utc> = Utc::now();
+
         conn.execute(
-            "INSERT OR IGNORE INTO item_tags (item_id, tag_id) VALUES (?1, ?2)",
-            params![item_id, tag_id],
+            "INSERT INTO clipboard_history
+                 (content_type, content, preview, created_at, source_format, content_hash,
+                  created_at_epoch, thumbnail, search_text, source_title)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)",
+            params![
+                content_type,
+                content,
+                preview,
+                now.to_rfc3339(),
+                source_format,
+                content_hash(content),
+                now.timestamp(),
+                thumbnail,
+                search_text_for(content_type, content, preview, source_title),
+                source_title,
+            ],
         )?;
-        Ok(())
+
+        let id = conn.last_insert_rowid();
+        self.record_audit(&conn, "add", Some(id), Some(content_type))?;
+        Ok(id)
     }
 
-    /// 移除项目标签
-    pub fn remove_item_tag(&self, item_id: i64, tag_name: &str) -> Result<()> {
+    /// 批量写入从外部导出文件解析出的记录（保留原始时间与收藏状态）。
+    /// 时间戳与内容哈希都相同的记录视为重复，类型不在记录范围内的记录同样跳过。
+    pub fn insert_imported_items(&self, entries: &[ImportedEntry]) -> Result<ImportedCounts> {
+        let conn = self.conn.lock().unwrap();
+        let tx = conn.unchecked_transaction()?;
+        let mut counts = ImportedCounts::default();
+
+        {
+            let mut stmt = tx.prepare(
+                "INSERT INTO clipboard_history
+                     (content_type, content, preview, is_favorite, created_at, content_hash,
+                      created_at_epoch, thumbnail, search_text)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+            )?;
+
+            for entry in entries {
+                if !self.allows_type(&entry.content_type) {
+                    counts.disallowed += 1;
+                    continue;
+                }
+
+                let hash = content_hash(&entry.content);
+                let epoch = rfc3339_epoch(&entry.created_at);
+
+                if let Some(epoch) = epoch {
+                    if Self::find_by_timestamp_and_content(&tx, epoch, &hash, &entry.content)?
+                        .is_some()
+                    {
+                        counts.duplicates += 1;
+                        continue;
+                    }
+                }
+
+                stmt.execute(params![
+                    entry.content_type,
+                    entry.content,
+                    entry.preview,
+                    entry.is_favorite as i64,
+                    entry.created_at,
+                    hash,
+                    epoch,
+                    entry.thumbnail,
+                    search_text_for(&entry.content_type, &entry.content, &entry.preview, None),
+                ])?;
+                self.record_audit(&tx, "add", Some(tx.last_insert_rowid()), Some("import"))?;
+                counts.inserted += 1;
+            }
+        }
+
+        tx.commit()?;
+        Ok(counts)
+    }
+
+    /// 在一个事务中把每个非空文本行写入为独立的文本记录，返回写入数量。
+    /// `skip_duplicates` 为 true 时跳过历史中（含本次导入）已存在相同内容的行。
+    pub fn insert_text_lines<I>(&self, lines: I, skip_duplicates: bool) -> Result<usize>
+    where
+        I: IntoIterator<Item = std::io::Result<String>>,
+    {
+        self.ensure_allowed("text")?;
+
+        let conn = self.conn.lock().unwrap();
+        let tx = conn.unchecked_transaction()?;
+        let now: DateTime<Utc> = Utc::now();
+        let mut inserted = 0;
+
+        {
+            let mut stmt = tx.prepare(
+                "INSERT INTO clipboard_history
+                     (content_type, content, preview, created_at, content_hash, created_at_epoch,
+                      search_text)
+                 VALUES ('text', ?1, ?2, ?3, ?4, ?5, ?6)",
+            )?;
+            let mut exists = tx.prepare(&format!(
+                "SELECT 1 FROM clipboard_history WHERE {SAME_CONTENT_FILTER} LIMIT 1"
+            ))?;
+
+            for line in lines {
+                let line = line?;
+                let text = line.trim_end_matches('\r');
+                if text.trim().is_empty() {
+                    continue;
+                }
+
+                let hash = content_hash(text);
+                if skip_duplicates && exists.exists(params![hash, text])? {
+                    continue;
+                }
+
+                let preview = build_text_preview(text, false);
+                stmt.execute(params![
+                    text,
+                    preview,
+                    now.to_rfc3339(),
+                    hash,
+                    now.timestamp(),
+                    search_text_for("text", text, &preview, None),
+                ])?;
+                self.record_audit(&tx, "add", Some(tx.last_insert_rowid()), Some("import"))?;
+                inserted += 1;
+            }
+        }
+
+        tx.commit()?;
+        Ok(inserted)
+    }
+
+    /// 获取历史记录总数
+    pub fn count_items(&self) -> Result<i64> {
+        let conn = self.conn.lock().unwrap();
+        let total = conn.query_row("SELECT COUNT(*) FROM clipboard_history", [], |row| {
+            row.get(0)
+        })?;
+        Ok(total)
+    }
+
+    /// 获取所有历史记录（带分页）
+    pub fn get_items(&self, limit: i64, offset: i64) -> Result<Vec<ClipboardItem>> {
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = conn.prepare(&format!(
+            "SELECT {ITEM_COLUMNS}
+                 FROM clipboard_history h
+                 ORDER BY h.created_at DESC
+                 LIMIT ?1 OFFSET ?2"
+        ))?;
+
+        let items = stmt
+            .query_map(params![limit, offset], item_from_row)?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        // 为每个项目获取标签
+        let mut items_with_tags = Vec::new();
+        for mut item in items {
+            item.tags = self.get_item_tags_internal(&conn, item.id)?;
+            items_with_tags.push(item);
+        }
+
+        Ok(items_with_tags)
+    }
+
+    /// 按 id 从大到小分页读取 id 小于 `before_id` 的记录（含标签），
+    /// 用于需要遍历全部记录又不能一次性载入全部内容的场景
+    pub fn get_items_before_id(&self, before_id: i64, limit: i64) -> Result<Vec<ClipboardItem>> {
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = conn.prepare(&format!(
+            "SELECT {ITEM_COLUMNS}
+                 FROM clipboard_history h
+                 WHERE h.id < ?1
+                 ORDER BY h.id DESC
+                 LIMIT ?2"
+        ))?;
+
+        let mut items = stmt
+            .query_map(params![before_id, limit], item_from_row)?
+            .collect::<Result<Vec<_>, _>>()?;
+        for item in &mut items {
+            item.tags = self.get_item_tags_internal(&conn, item.id)?;
+        }
+
+        Ok(items)
+    }
+
+    /// 获取历史记录列表，标签附带 id
+    pub fn get_items_with_tag_ids(&self, limit: i64, offset: i64) -> Result<Vec<ItemWithTagIds>> {
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = conn.prepare(&format!(
+            "SELECT {ITEM_COLUMNS}
+                 FROM clipboard_history h
+                 ORDER BY h.created_at DESC
+                 LIMIT ?1 OFFSET ?2"
+        ))?;
+
+        let items = stmt
+            .query_map(params![limit, offset], item_from_row)?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let mut items_with_tags = Vec::new();
+        for mut item in items {
+            let tag_refs = self.get_item_tag_refs_internal(&conn, item.id)?;
+            item.tags = tag_refs.iter().map(|tag| tag.name.clone()).collect();
+            items_with_tags.push(ItemWithTagIds { item, tag_refs });
+        }
+
+        Ok(items_with_tags)
+    }
+
+    /// 记录一次从历史中复制该条目
+    pub fn record_copy(&self, id: i64) -> Result<()> {
         let conn = self.conn.lock().unwrap();
         conn.execute(
-            "DELETE FROM item_tags 
-             WHERE item_id = ?1 
-             AND tag_id = (SELECT id FROM tags WHERE name = ?2)",
-            params![item_id, tag_name],
+            "UPDATE clipboard_history SET copy_count = copy_count + 1 WHERE id = ?1",
+            params![id],
         )?;
         Ok(())
     }
 
-    /// 获取项目的所有标签（内部方法，用于已有连接）
-    fn get_item_tags_internal(&self, conn: &Connection, item_id: i64) -> Result<Vec<String>> {
-        let mut stmt = conn.prepare(
-            "SELECT t.name FROM tags t
-             JOIN item_tags it ON t.id = it.tag_id
-             WHERE it.item_id = ?1",
-        )?;
+    /// 按综合得分排序获取记录：新近程度按天衰减（`recency / (1 + 天数)`），
+    /// 加上使用次数与收藏的加权分
+    pub fn get_ranked(&self, limit: i64, weights: RankingWeights) -> Result<Vec<ClipboardItem>> {
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = conn.prepare(&format!(
+            "SELECT {ITEM_COLUMNS}
+             FROM clipboard_history h
+             ORDER BY ?1 / (1.0 + MAX(?4 - IFNULL({EPOCH_EXPR}, 0), 0) / 86400.0)
+                      + ?2 * h.copy_count
+                      + ?3 * h.is_favorite DESC,
+                      h.created_at DESC
+             LIMIT ?5"
+        ))?;
 
-        let tags = stmt
-            .query_map(params![item_id], |row| row.get(0))?
-            .collect::<Result<Vec<String>, _>>()?;
+        let items = stmt
+            .query_map(
+                params![
+                    weights.recency,
+                    weights.usage,
+                    weights.favorite,
+                    Utc::now().timestamp(),
+                    limit
+                ],
+                item_from_row,
+            )?
+            .collect::<Result<Vec<_>, _>>()?;
 
-        Ok(tags)
+        let mut items_with_tags = Vec::with_capacity(items.len());
+        for mut item in items {
+            item.tags = self.get_item_tags_internal(&conn, item.id)?;
+            items_with_tags.push(item);
+        }
+
+        Ok(items_with_tags)
     }
 
-    /// 获取所有标签
-    pub fn get_all_tags(&self) -> Result<Vec<String>> {
+    /// 获取没有任何标签的记录（分页方式与 `get_items` 一致）
+    pub fn get_untagged_items(&self, limit: i64, offset: i64) -> Result<Vec<ClipboardItem>> {
         let conn = self.conn.lock().unwrap();
-        let mut stmt = conn.prepare("SELECT name FROM tags ORDER BY name")?;
-        
-        let tags = stmt
-            .query_map([], |row| row.get(0))?
-            .collect::<Result<Vec<String>, _>>()?;
+        let mut stmt = conn.prepare(&format!(
+            "SELECT {ITEM_COLUMNS}
+             FROM clipboard_history h
+             LEFT JOIN item_tags it ON it.item_id = h.id
+             WHERE it.item_id IS NULL
+             ORDER BY h.created_at DESC
+             LIMIT ?1 OFFSET ?2"
+        ))?;
 
-        Ok(tags)
+        let items = stmt
+            .query_map(params![limit, offset], item_from_row)?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(items)
     }
 
-    /// 按标签获取项目
-    pub fn get_items_by_tag(&self, tag_name: &str, limit: i64) -> Result<Vec<ClipboardItem>> {
+    /// 按快速筛选获取记录（按时间从新到旧）
+    pub fn get_filtered(&self, filter: &HistoryFilter, limit: i64) -> Result<Vec<ClipboardItem>> {
+        match filter {
+            HistoryFilter::All => self.get_items(limit, 0),
+            HistoryFilter::Untagged => self.get_untagged_items(limit, 0),
+            HistoryFilter::Tag(tag) => self.get_items_by_tag(tag, limit),
+            HistoryFilter::FavoriteTag(tag) => self.get_favorites_by_tag(tag, limit),
+            HistoryFilter::Favorites => self.query_items_matching(true, None, limit),
+            HistoryFilter::ContentType(content_type) => {
+                self.query_items_matching(false, Some(content_type), limit)
+            }
+        }
+    }
+
+    fn query_items_matching(
+        &self,
+        favorites_only: bool,
+        content_type: Option<&str>,
+        limit: i64,
+    ) -> Result<Vec<ClipboardItem>> {
         let conn = self.conn.lock().unwrap();
-        let mut stmt = conn.prepare(
-            "SELECT h.id, h.content_type, h.content, h.preview, h.is_favorite, h.created_at
+        let mut stmt = conn.prepare(&format!(
+            "SELECT {ITEM_COLUMNS}
              FROM clipboard_history h
-             JOIN item_tags it ON h.id = it.item_id
-             JOIN tags t ON it.tag_id = t.id
-             WHERE t.name = ?1
+             WHERE (?1 = 0 OR h.is_favorite = 1) AND (?2 IS NULL OR h.content_type = ?2)
              ORDER BY h.created_at DESC
-             LIMIT ?2",
+             LIMIT ?3"
+        ))?;
+
+        let items = stmt
+            .query_map(params![favorites_only, content_type, limit], item_from_row)?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let mut items_with_tags = Vec::new();
+        for mut item in items {
+            item.tags = self.get_item_tags_internal(&conn, item.id)?;
+            items_with_tags.push(item);
+        }
+
+        Ok(items_with_tags)
+    }
+
+    /// 按给定顺序获取多条记录，不存在的 id 会被忽略
+    pub fn get_items_by_ids(&self, ids: &[i64]) -> Result<Vec<ClipboardItem>> {
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = conn.prepare(&format!(
+            "SELECT {ITEM_COLUMNS} FROM clipboard_history h WHERE h.id = ?1"
+        ))?;
+
+        let mut items = Vec::with_capacity(ids.len());
+        for id in ids {
+            if let Some(mut item) = stmt.query_row(params![id], item_from_row).optional()? {
+                item.tags = self.get_item_tags_internal(&conn, item.id)?;
+                items.push(item);
+            }
+        }
+
+        Ok(items)
+    }
+
+    /// 按一天中的小时统计记录数量，`offset_minutes` 为相对 UTC 的时区偏移
+    pub fn get_hourly_histogram(&self, offset_minutes: i64) -> Result<[i64; 24]> {
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = conn.prepare(&format!(
+            "SELECT (({EPOCH_EXPR} + ?1) % 86400 + 86400) % 86400 / 3600 AS hour, COUNT(*)
+             FROM clipboard_history
+             WHERE {EPOCH_EXPR} IS NOT NULL
+             GROUP BY hour"
+        ))?;
+
+        let mut histogram = [0i64; 24];
+        let rows = stmt.query_map(params![offset_minutes * 60], |row| {
+            Ok((row.get::<_, i64>(0)?, row.get::<_, i64>(1)?))
+        })?;
+        for row in rows {
+            let (hour, count) = row?;
+            if let Some(bucket) = histogram.get_mut(hour as usize) {
+                *bucket = count;
+            }
+        }
+
+        Ok(histogram)
+    }
+
+    /// 获取单条记录内容的 UTF-8 字节数
+    pub fn get_item_size(&self, id: i64) -> Result<i64> {
+        let conn = self.conn.lock().unwrap();
+        let size = conn
+            .query_row(
+                "SELECT length(CAST(content AS BLOB)) FROM clipboard_history WHERE id = ?1",
+                params![id],
+                |row| row.get(0),
+            )
+            .optional()?
+            .ok_or_else(|| anyhow!("Item {id} not found"))?;
+        Ok(size)
+    }
+
+    /// 获取内容最大的若干条记录（按 UTF-8 字节数降序）
+    pub fn get_largest_items(&self, limit: i64) -> Result<Vec<ItemSummary>> {
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = conn.prepare(
+            "SELECT id, content_type, preview, is_favorite, created_at,
+                    length(CAST(content AS BLOB)) AS byte_size
+             FROM clipboard_history
+             ORDER BY byte_size DESC, id DESC
+             LIMIT ?1",
         )?;
 
         let items = stmt
-            .query_map(params![tag_name, limit], |row| {
-                let item_id: i64 = row.get(0)?;
-                Ok(ClipboardItem {
-                    id: item_id,
+            .query_map(params![limit], |row| {
+                Ok(ItemSummary {
+                    id: row.get(0)?,
                     content_type: row.get(1)?,
-                    content: row.get(2)?,
-                    preview: row.get(3)?,
-                    is_favorite: row.get::<_, i64>(4)? != 0,
-                    tags: Vec::new(),
-                    created_at: row.get(5)?,
+                    preview: row.get(2)?,
+                    is_favorite: row.get::<_, i64>(3)? != 0,
+                    created_at: row.get(4)?,
+                    byte_size: row.get(5)?,
                 })
             })?
             .collect::<Result<Vec<_>, _>>()?;
 
-        let mut items_with_tags = Vec::new();
+        Ok(items)
+    }
+
+    /// 搜索历史记录
+    pub fn search_items(&self, query: &str, limit: i64) -> Result<Vec<ClipboardItem>> {
+        let trimmed = query.trim();
+        if trimmed.is_empty() {
+            return self.get_items(limit, 0);
+        }
+
+        let conn = self.conn.lock().unwrap();
+        let like_pattern = match build_like_pattern(trimmed) {
+            Some(pattern) => pattern,
+            None => return Ok(Vec::new()),
+        };
+        let like_param = like_pattern.to_lowercase();
+        let folded_param = match build_like_pattern(&fold_search_text(trimmed)) {
+            Some(pattern) => pattern,
+            None => like_param.clone(),
+        };
+
+        let mut stmt = conn.prepare(&format!(
+            "SELECT DISTINCT {ITEM_COLUMNS}
+                 FROM clipboard_history h
+                 LEFT JOIN item_tags it ON h.id = it.item_id
+                 LEFT JOIN tags t ON it.tag_id = t.id
+                 WHERE h.search_text LIKE ?2 ESCAPE '\\'
+                    OR (h.search_text IS NULL
+                        AND (LOWER(h.content) LIKE ?1 ESCAPE '\\'
+                             OR LOWER(h.preview) LIKE ?1 ESCAPE '\\'))
+                    OR LOWER(IFNULL(t.name, '')) LIKE ?1 ESCAPE '\\'
+                    OR LOWER(IFNULL(t.name, '')) LIKE ?2 ESCAPE '\\'
+                 ORDER BY h.is_favorite DESC, h.created_at DESC
+                 LIMIT ?3"
+        ))?;
+
+        let items = stmt
+            .query_map(params![like_param, folded_param, limit], item_from_row)?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let mut items_with_tags = Vec::with_capacity(items.len());
         for mut item in items {
             item.tags = self.get_item_tags_internal(&conn, item.id)?;
             items_with_tags.push(item);
@@ -420,4 +1395,2017 @@ impl Database {
 
         Ok(items_with_tags)
     }
+
+    /// 用正则表达式搜索文本与文件记录（图片内容为 base64，不参与匹配）。
+    /// 只扫描最新的 `REGEX_SCAN_LIMIT` 条，并在超过 `REGEX_SCAN_BUDGET` 后停止
+    pub fn search_regex(&self, pattern: &str, limit: i64) -> Result<Vec<ClipboardItem>> {
+        self.search_regex_within(pattern, limit, REGEX_SCAN_LIMIT, REGEX_SCAN_BUDGET)
+    }
+
+    fn search_regex_within(
+        &self,
+        pattern: &str,
+        limit: i64,
+        scan_limit: i64,
+        budget: std::time::Duration,
+    ) -> Result<Vec<ClipboardItem>> {
+        let regex = RegexBuilder::new(pattern)
+            .size_limit(REGEX_SIZE_LIMIT)
+            .build()
+            .map_err(|err| anyhow!("Invalid regular expression: {err}"))?;
+        let limit = usize::try_from(limit).unwrap_or(0);
+        if limit == 0 {
+            return Ok(Vec::new());
+        }
+
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = conn.prepare(&format!(
+            "SELECT {ITEM_COLUMNS}
+             FROM clipboard_history h
+             WHERE h.content_type != 'image'
+             ORDER BY h.created_at DESC
+             LIMIT ?1"
+        ))?;
+
+        let started = std::time::Instant::now();
+        let mut matches = Vec::new();
+        for item in stmt.query_map(params![scan_limit], item_from_row)? {
+            let mut item = item?;
+            if regex.is_match(&item.content) {
+                item.tags = self.get_item_tags_internal(&conn, item.id)?;
+                matches.push(item);
+                if matches.len() >= limit {
+                    break;
+                }
+            }
+            // 用 >= 判断：零预算时扫描一行后必定停止，不依赖时钟精度
+            if started.elapsed() >= budget {
+                break;
+            }
+        }
+
+        Ok(matches)
+    }
+
+    /// 查找包含指定文件路径的文件记录（按时间从新到旧），便于移动或删除文件后清理历史
+    pub fn find_items_referencing_path(&self, path: &str) -> Result<Vec<ClipboardItem>> {
+        let target = normalize_path_for_match(path, cfg!(windows));
+        if target.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = conn.prepare(&format!(
+            "SELECT {ITEM_COLUMNS}
+             FROM clipboard_history h
+             WHERE h.content_type = 'file'
+             ORDER BY h.created_at DESC"
+        ))?;
+
+        let items = stmt
+            .query_map([], item_from_row)?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let mut matches = Vec::new();
+        for mut item in items {
+            let Ok(paths) = serde_json::from_str::<Vec<String>>(&item.content) else {
+                continue;
+            };
+            if paths
+                .iter()
+                .any(|candidate| normalize_path_for_match(candidate, cfg!(windows)) == target)
+            {
+                item.tags = self.get_item_tags_internal(&conn, item.id)?;
+                matches.push(item);
+            }
+        }
+
+        Ok(matches)
+    }
+
+    /// 获取列表顺序中位于指定记录之后（更早）的一条，`wrap` 时末尾回到第一条
+    pub fn get_next_item(&self, after_id: i64, wrap: bool) -> Result<Option<ClipboardItem>> {
+        self.get_adjacent_item(after_id, wrap, true)
+    }
+
+    /// 获取列表顺序中位于指定记录之前（更新）的一条，`wrap` 时开头回到最后一条
+    pub fn get_prev_item(&self, before_id: i64, wrap: bool) -> Result<Option<ClipboardItem>> {
+        self.get_adjacent_item(before_id, wrap, false)
+    }
+
+    fn get_adjacent_item(&self, id: i64, wrap: bool, older: bool) -> Result<Option<ClipboardItem>> {
+        let conn = self.conn.lock().unwrap();
+
+        let created_at: Option<String> = conn
+            .query_row(
+                "SELECT created_at FROM clipboard_history WHERE id = ?1",
+                params![id],
+                |row| row.get(0),
+            )
+            .optional()?;
+        let Some(created_at) = created_at else {
+            return Ok(None);
+        };
+
+        let (condition, order) = if older {
+            (
+                "h.created_at < ?1 OR (h.created_at = ?1 AND h.id < ?2)",
+                "DESC",
+            )
+        } else {
+            (
+                "h.created_at > ?1 OR (h.created_at = ?1 AND h.id > ?2)",
+                "ASC",
+            )
+        };
+
+        let mut item = conn
+            .query_row(
+                &format!(
+                    "SELECT {ITEM_COLUMNS} FROM clipboard_history h
+                     WHERE {condition}
+                     ORDER BY h.created_at {order}, h.id {order}
+                     LIMIT 1"
+                ),
+                params![created_at, id],
+                item_from_row,
+            )
+            .optional()?;
+
+        if item.is_none() && wrap {
+            item = conn
+                .query_row(
+                    &format!(
+                        "SELECT {ITEM_COLUMNS} FROM clipboard_history h
+                         ORDER BY h.created_at {order}, h.id {order}
+                         LIMIT 1"
+                    ),
+                    [],
+                    item_from_row,
+                )
+                .optional()?;
+        }
+
+        match item {
+            Some(mut item) => {
+                item.tags = self.get_item_tags_internal(&conn, item.id)?;
+                Ok(Some(item))
+            }
+            None => Ok(None),
+        }
+    }
+
+    /// 切换收藏状态
+    pub fn toggle_favorite(&self, id: i64) -> Result<bool> {
+        let conn = self.conn.lock().unwrap();
+        let is_favorite: i64 = conn
+            .query_row(
+                "SELECT is_favorite FROM clipboard_history WHERE id = ?1",
+                params![id],
+                |row| row.get(0),
+            )
+            .optional()?
+            .unwrap_or(0);
+
+        let new_state = if is_favorite == 0 { 1 } else { 0 };
+
+        conn.execute(
+            "UPDATE clipboard_history SET is_favorite = ?1 WHERE id = ?2",
+            params![new_state, id],
+        )?;
+
+        let op = if new_state != 0 {
+            "favorite"
+        } else {
+            "unfavorite"
+        };
+        self.record_audit(&conn, op, Some(id), None)?;
+        Ok(new_state != 0)
+    }
+
+    /// 将记录的时间更新为当前时间，使其回到历史列表顶部
+    pub fn touch_item(&self, id: i64) -> Result<()> {
+        let conn = self.conn.lock().unwrap();
+        let now: DateTime<Utc> = Utc::now();
+
+        let updated = conn.execute(
+            "UPDATE clipboard_history SET created_at = ?1, created_at_epoch = ?2 WHERE id = ?3",
+            params![now.to_rfc3339(), now.timestamp(), id],
+        )?;
+        if updated == 0 {
+            return Err(anyhow!("Item {id} not found"));
+        }
+
+        self.record_audit(&conn, "touch", Some(id), None)?;
+        Ok(())
+    }
+
+    /// 原地替换文本记录的内容与预览，在同一条语句中重算哈希与检索文本（全文索引由更新触发器同步）。
+    /// 预览由调用方按当前配置生成，与捕获时一致。`bump` 为 true 时同时把时间更新为当前时间。
+    pub fn update_content(
+        &self,
+        id: i64,
+        new_content: &str,
+        preview: &str,
+        bump: bool,
+    ) -> Result<()> {
+        if new_content.trim().is_empty() {
+            return Err(anyhow!("Content must not be empty"));
+        }
+
+        let conn = self.conn.lock().unwrap();
+        let (content_type, source_title): (String, Option<String>) = conn
+            .query_row(
+                "SELECT content_type, source_title FROM clipboard_history WHERE id = ?1",
+                params![id],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )
+            .optional()?
+            .ok_or_else(|| anyhow!("Item {id} not found"))?;
+        if content_type != "text" {
+            return Err(anyhow!("Only text items can be edited"));
+        }
+
+        let now: DateTime<Utc> = Utc::now();
+        conn.execute(
+            "UPDATE clipboard_history
+             SET content = ?1, preview = ?2, content_hash = ?3, search_text = ?4,
+                 created_at = CASE WHEN ?5 THEN ?6 ELSE created_at END,
+                 created_at_epoch = CASE WHEN ?5 THEN ?7 ELSE created_at_epoch END
+             WHERE id = ?8",
+            params![
+                new_content,
+                preview,
+                content_hash(new_content),
+                search_text_for(&content_type, new_content, preview, source_title.as_deref()),
+                bump,
+                now.to_rfc3339(),
+                now.timestamp(),
+                id,
+            ],
+        )?;
+
+        self.record_audit(&conn, "edit", Some(id), None)?;
+        Ok(())
+    }
+
+    /// 删除记录
+    pub fn delete_item(&self, id: i64) -> Result<()> {
+        let conn = self.conn.lock().unwrap();
+        conn.execute("DELETE FROM clipboard_history WHERE id = ?1", params![id])?;
+        self.record_audit(&conn, "delete", Some(id), None)?;
+        Ok(())
+    }
+
+    /// 清空所有非收藏的历史记录；`to_trash` 为 true 时移入回收站而不是直接删除
+    pub fn clear_non_favorites(&self, to_trash: bool) -> Result<()> {
+        let conn = self.conn.lock().unwrap();
+        let tx = conn.unchecked_transaction()?;
+
+        if to_trash {
+            tx.execute(
+                &format!(
+                    "INSERT OR REPLACE INTO clipboard_trash ({TRASH_COLUMNS}, tags, deleted_at)
+                     SELECT {TRASH_COLUMNS},
+                            (SELECT json_group_array(t.name) FROM item_tags it
+                             JOIN tags t ON it.tag_id = t.id
+                             WHERE it.item_id = clipboard_history.id),
+                            ?1
+                     FROM clipboard_history WHERE is_favorite = 0"
+                ),
+                params![Utc::now().to_rfc3339()],
+            )?;
+        }
+
+        tx.execute("DELETE FROM clipboard_history WHERE is_favorite = 0", [])?;
+        self.record_audit(&tx, "clear", None, None)?;
+        tx.commit()?;
+        Ok(())
+    }
+
+    /// 删除全部历史记录（含收藏）与回收站，保留标签定义
+    pub fn clear_all_history(&self) -> Result<()> {
+        let conn = self.conn.lock().unwrap();
+        let tx = conn.unchecked_transaction()?;
+
+        tx.execute("DELETE FROM clipboard_history", [])?;
+        tx.execute("DELETE FROM clipboard_trash", [])?;
+        self.record_audit(&tx, "clear", None, Some("all"))?;
+        tx.commit()?;
+        Ok(())
+    }
+
+    /// 执行退出时的清空；内容不进入回收站，以免在共享电脑上残留
+    pub fn clear_for_exit(&self, action: ExitClearAction) -> Result<()> {
+        match action {
+            ExitClearAction::NonFavorites => self.clear_non_favorites(false),
+            ExitClearAction::All => self.clear_all_history(),
+        }
+    }
+
+    /// 获取回收站中尚未过期的记录
+    pub fn get_trash(&self, retention_minutes: i64) -> Result<Vec<TrashedItem>> {
+        let conn = self.conn.lock().unwrap();
+        Self::purge_trash_internal(&conn, retention_minutes)?;
+
+        let mut stmt = conn.prepare(&format!(
+            "SELECT {ITEM_COLUMNS}, h.tags, h.deleted_at
+             FROM clipboard_trash h
+             ORDER BY h.deleted_at DESC, h.created_at DESC"
+        ))?;
+
+        let items = stmt
+            .query_map([], |row| {
+                let mut item = item_from_row(row)?;
+                let tags: String = row.get(9)?;
+                item.tags = serde_json::from_str(&tags).unwrap_or_default();
+                Ok(TrashedItem {
+                    item,
+                    deleted_at: row.get(10)?,
+                })
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(items)
+    }
+
+    /// 从回收站恢复记录（连同标签），`ids` 为空时恢复全部，返回恢复的数量。
+    /// 当前允许列表不再接受的类型留在回收站中，直至过期清除
+    pub fn restore_from_trash(&self, ids: &[i64], retention_minutes: i64) -> Result<usize> {
+        let conn = self.conn.lock().unwrap();
+        Self::purge_trash_internal(&conn, retention_minutes)?;
+        let tx = conn.unchecked_transaction()?;
+
+        let ids: Vec<i64> = if ids.is_empty() {
+            let mut stmt = tx.prepare("SELECT id FROM clipboard_trash")?;
+            let all = stmt
+                .query_map([], |row| row.get(0))?
+                .collect::<Result<Vec<i64>, _>>()?;
+            all
+        } else {
+            ids.to_vec()
+        };
+
+        let mut restored = 0;
+        for id in ids {
+            let trashed: Option<(String, String)> = tx
+                .query_row(
+                    "SELECT content_type, tags FROM clipboard_trash WHERE id = ?1",
+                    params![id],
+                    |row| Ok((row.get(0)?, row.get(1)?)),
+                )
+                .optional()?;
+            let Some((content_type, tags)) = trashed else {
+                continue;
+            };
+            if !self.allows_type(&content_type) {
+                continue;
+            }
+
+            tx.execute(
+                &format!(
+                    "INSERT INTO clipboard_history ({TRASH_COLUMNS})
+                     SELECT {TRASH_COLUMNS} FROM clipboard_trash WHERE id = ?1"
+                ),
+                params![id],
+            )?;
+
+            for tag in serde_json::from_str::<Vec<String>>(&tags).unwrap_or_default() {
+                tx.execute(
+                    "INSERT OR IGNORE INTO tags (name) VALUES (?1)",
+                    params![tag],
+                )?;
+                tx.execute(
+                    "INSERT OR IGNORE INTO item_tags (item_id, tag_id)
+                     SELECT ?1, id FROM tags WHERE name = ?2",
+                    params![id, tag],
+                )?;
+            }
+
+            tx.execute("DELETE FROM clipboard_trash WHERE id = ?1", params![id])?;
+            restored += 1;
+        }
+
+        tx.commit()?;
+        // 旧版本放入回收站的记录没有搜索文本
+        Self::fill_search_text(&conn, false)?;
+        Ok(restored)
+    }
+
+    /// 永久删除超过保留期的回收站记录（内部方法，用于已有连接）
+    fn purge_trash_internal(conn: &Connection, retention_minutes: i64) -> Result<usize> {
+        let cutoff = (Utc::now() - Duration::minutes(retention_minutes.max(0))).to_rfc3339();
+        let removed = conn.execute(
+            "DELETE FROM clipboard_trash WHERE deleted_at < ?1",
+            params![cutoff],
+        )?;
+        Ok(removed)
+    }
+
+    /// 删除早于指定天数的非收藏记录（内部方法，用于已有连接）
+    fn prune_older_than_internal(conn: &Connection, days: i64) -> Result<usize> {
+        if days <= 0 {
+            return Ok(0);
+        }
+
+        let cutoff = (Utc::now() - Duration::days(days)).to_rfc3339();
+        let removed = conn.execute(
+            "DELETE FROM clipboard_history WHERE is_favorite = 0 AND created_at < ?1",
+            params![cutoff],
+        )?;
+        Ok(removed)
+    }
+
+    /// 删除早于指定天数的非收藏图片记录，文本与文件记录不受影响
+    pub fn prune_images_older_than(&self, days: i64) -> Result<ImagePruneReport> {
+        if days <= 0 {
+            return Ok(ImagePruneReport::default());
+        }
+
+        let cutoff = (Utc::now() - Duration::days(days)).timestamp();
+        let conn = self.conn.lock().unwrap();
+        let tx = conn.unchecked_transaction()?;
+
+        let condition = format!("content_type = 'image' AND is_favorite = 0 AND {EPOCH_EXPR} < ?1");
+        let bytes_reclaimed: i64 = tx.query_row(
+            &format!(
+                "SELECT IFNULL(SUM(length(CAST(content AS BLOB))
+                                   + IFNULL(length(CAST(thumbnail AS BLOB)), 0)), 0)
+                 FROM clipboard_history WHERE {condition}"
+            ),
+            params![cutoff],
+            |row| row.get(0),
+        )?;
+        let removed = tx.execute(
+            &format!("DELETE FROM clipboard_history WHERE {condition}"),
+            params![cutoff],
+        )?;
+
+        if removed > 0 {
+            self.record_audit(&tx, "prune_images", None, Some(&removed.to_string()))?;
+        }
+        tx.commit()?;
+
+        Ok(ImagePruneReport {
+            removed,
+            bytes_reclaimed,
+        })
+    }
+
+    /// 最近一次后台维护的时间，从未维护过时返回 None
+    pub fn last_maintenance_run(&self) -> Result<Option<DateTime<Utc>>> {
+        let conn = self.conn.lock().unwrap();
+        let last_run = Self::get_meta(&conn, META_MAINTENANCE_LAST_RUN)?
+            .and_then(|value| value.parse::<i64>().ok())
+            .and_then(|secs| DateTime::from_timestamp(secs, 0));
+        Ok(last_run)
+    }
+
+    /// 记录后台维护的时间，重启后据此判断下一次维护是否到期
+    pub fn record_maintenance_run(&self, at: DateTime<Utc>) -> Result<()> {
+        let conn = self.conn.lock().unwrap();
+        Self::set_meta(
+            &conn,
+            META_MAINTENANCE_LAST_RUN,
+            &at.timestamp().to_string(),
+        )
+    }
+
+    /// 执行后台维护：按时间清理、优化全文索引、碎片较多时压缩数据库。
+    /// 整个过程持有连接锁，因此不会与导入等批量写入交错执行。
+    pub fn run_maintenance(&self, options: MaintenanceOptions) -> Result<MaintenanceSummary> {
+        let conn = self.conn.lock().unwrap();
+        let mut summary = MaintenanceSummary {
+            pruned: Self::prune_older_than_internal(&conn, options.retention_days)?,
+            trash_purged: Self::purge_trash_internal(&conn, options.trash_retention_minutes)?,
+            audit_trimmed: Self::trim_audit_log_internal(&conn, options.audit_retention_days)?,
+            ..Default::default()
+        };
+
+        conn.execute(
+            "INSERT INTO clipboard_fts(clipboard_fts) VALUES('optimize')",
+            [],
+        )?;
+        summary.fts_optimized = true;
+
+        let page_count: i64 = conn.query_row("PRAGMA page_count", [], |row| row.get(0))?;
+        let freelist_count: i64 = conn.query_row("PRAGMA freelist_count", [], |row| row.get(0))?;
+
+        if page_count > 0 && freelist_count as f64 / page_count as f64 > VACUUM_FREELIST_RATIO {
+            conn.execute("VACUUM", [])?;
+            summary.vacuumed = true;
+        }
+
+        Ok(summary)
+    }
+
+    /// 维护历史记录数量上限，返回被裁剪的记录数
+    pub fn maintain_limit(&self, max_items: i64) -> Result<usize> {
+        let conn = self.conn.lock().unwrap();
+        if max_items <= 0 {
+            return Ok(conn.execute("DELETE FROM clipboard_history", [])?);
+        }
+
+        let total: i64 = conn.query_row("SELECT COUNT(*) FROM clipboard_history", [], |row| {
+            row.get(0)
+        })?;
+
+        if total <= max_items {
+            return Ok(0);
+        }
+
+        let to_remove = total - max_items;
+
+        let removed_non_favorites = conn.execute(
+            "DELETE FROM clipboard_history WHERE id IN (
+                 SELECT id FROM clipboard_history
+                 WHERE is_favorite = 0
+                 ORDER BY created_at ASC, id ASC
+                 LIMIT ?1
+             )",
+            params![to_remove],
+        )?;
+
+        let remaining = to_remove.saturating_sub(removed_non_favorites as i64);
+
+        let removed_favorites = if remaining > 0 {
+            conn.execute(
+                "DELETE FROM clipboard_history WHERE id IN (
+                     SELECT id FROM clipboard_history
+                     ORDER BY created_at ASC, id ASC
+                     LIMIT ?1
+                 )",
+                params![remaining],
+            )?
+        } else {
+            0
+        };
+        Ok(removed_non_favorites + removed_favorites)
+    }
+
+    /// 添加标签
+    pub fn add_tag(&self, name: &str) -> Result<i64> {
+        let conn = self.conn.lock().unwrap();
+        conn.execute(
+            "INSERT OR IGNORE INTO tags (name) VALUES (?1)",
+            params![name],
+        )?;
+
+        let tag_id: i64 = conn.query_row(
+            "SELECT id FROM tags WHERE name = ?1",
+            params![name],
+            |row| row.get(0),
+        )?;
+
+        Ok(tag_id)
+    }
+
+    /// 为项目添加标签
+    pub fn add_item_tag(&self, item_id: i64, tag_name: &str) -> Result<()> {
+        let tag_id = self.add_tag(tag_name)?;
+        let conn = self.conn.lock().unwrap();
+        conn.execute(
+            "INSERT OR IGNORE INTO item_tags (item_id, tag_id) VALUES (?1, ?2)",
+            params![item_id, tag_id],
+        )?;
+        self.record_audit(&conn, "tag", Some(item_id), Some(tag_name))?;
+        Ok(())
+    }
+
+    /// 移除项目标签
+    pub fn remove_item_tag(&self, item_id: i64, tag_name: &str) -> Result<()> {
+        let conn = self.conn.lock().unwrap();
+        conn.execute(
+            "DELETE FROM item_tags 
+             WHERE item_id = ?1 
+             AND tag_id = (SELECT id FROM tags WHERE name = ?2)",
+            params![item_id, tag_name],
+        )?;
+        self.record_audit(&conn, "untag", Some(item_id), Some(tag_name))?;
+        Ok(())
+    }
+
+    /// 删除标签。指定 `reassign_to` 时先把该标签下的记录改挂到目标标签（不存在则创建），
+    /// 已同时拥有两个标签的记录不会产生重复关联
+    pub fn delete_tag(&self, name: &str, reassign_to: Option<&str>) -> Result<()> {
+        let reassign_to = reassign_to.map(str::trim);
+        if reassign_to == Some("") {
+            return Err(anyhow!("Reassignment target must not be empty"));
+        }
+        if reassign_to == Some(name) {
+            return Err(anyhow!("Cannot reassign tag {name} to itself"));
+        }
+
+        let conn = self.conn.lock().unwrap();
+        let tx = conn.unchecked_transaction()?;
+
+        let Some(tag_id) = tx
+            .query_row(
+                "SELECT id FROM tags WHERE name = ?1",
+                params![name],
+                |row| row.get::<_, i64>(0),
+            )
+            .optional()?
+        else {
+            return Err(anyhow!("Tag {name} not found"));
+        };
+
+        if let Some(target) = reassign_to {
+            tx.execute(
+                "INSERT OR IGNORE INTO tags (name) VALUES (?1)",
+                params![target],
+            )?;
+            // 已经带有目标标签的记录只需删除旧关联
+            tx.execute(
+                "INSERT INTO item_tags (item_id, tag_id)
+                 SELECT it.item_id, target.id
+                 FROM item_tags it, tags target
+                 WHERE it.tag_id = ?1 AND target.name = ?2
+                   AND NOT EXISTS (
+                       SELECT 1 FROM item_tags existing
+                       WHERE existing.item_id = it.item_id AND existing.tag_id = target.id
+                   )",
+                params![tag_id, target],
+            )?;
+        }
+
+        // item_tags 中的旧关联通过外键级联删除
+        tx.execute("DELETE FROM tags WHERE id = ?1", params![tag_id])?;
+
+        let detail = match reassign_to {
+            Some(target) => format!("{name} -> {target}"),
+            None => name.to_string(),
+        };
+        self.record_audit(&tx, "delete_tag", None, Some(&detail))?;
+
+        tx.commit()?;
+        Ok(())
+    }
+
+    /// 合并仅大小写不同的标签：关联改挂到保留的标签上（自动去重）并删除多余的标签，
+    /// 返回被合并删除的标签数量。没有大小写重复的标签不会被改名
+    pub fn normalize_tag_case(&self, policy: TagCasePolicy) -> Result<usize> {
+        let conn = self.conn.lock().unwrap();
+        let tx = conn.unchecked_transaction()?;
+
+        let tags = {
+            let mut stmt = tx.prepare("SELECT id, name FROM tags ORDER BY id")?;
+            let rows = stmt
+                .query_map([], |row| {
+                    Ok((row.get::<_, i64>(0)?, row.get::<_, String>(1)?))
+                })?
+                .collect::<Result<Vec<_>, _>>()?;
+            rows
+        };
+
+        // 按小写名称分组，组内保持创建顺序
+        let mut groups: Vec<(String, Vec<(i64, String)>)> = Vec::new();
+        for (id, name) in tags {
+            let key = name.to_lowercase();
+            match groups.iter_mut().find(|(existing, _)| *existing == key) {
+                Some((_, members)) => members.push((id, name)),
+                None => groups.push((key, vec![(id, name)])),
+            }
+        }
+
+        // 只处理确有多种写法的组，没有大小写重复的标签保持原样
+        let mut merged = 0;
+        for (lowered, members) in groups.into_iter().filter(|(_, members)| members.len() > 1) {
+            let (keep_id, keep_name) = &members[0];
+            let canonical = match policy {
+                TagCasePolicy::Lower => lowered,
+                TagCasePolicy::PreserveFirst => keep_name.clone(),
+            };
+
+            for (duplicate_id, _) in &members[1..] {
+                tx.execute(
+                    "INSERT OR IGNORE INTO item_tags (item_id, tag_id)
+                     SELECT item_id, ?1 FROM item_tags WHERE tag_id = ?2",
+                    params![keep_id, duplicate_id],
+                )?;
+                tx.execute("DELETE FROM tags WHERE id = ?1", params![duplicate_id])?;
+                merged += 1;
+            }
+
+            if *keep_name != canonical {
+                tx.execute(
+                    "UPDATE tags SET name = ?1 WHERE id = ?2",
+                    params![canonical, keep_id],
+                )?;
+            }
+        }
+
+        if merged > 0 {
+            self.record_audit(&tx, "normalize_tags", None, Some(&merged.to_string()))?;
+        }
+        tx.commit()?;
+        Ok(merged)
+    }
+
+    /// 获取项目的所有标签（内部方法，用于已有连接）
+    fn get_item_tags_internal(&self, conn: &Connection, item_id: i64) -> Result<Vec<String>> {
+        Ok(self
+            .get_item_tag_refs_internal(conn, item_id)?
+            .into_iter()
+            .map(|tag| tag.name)
+            .collect())
+    }
+
+    /// 获取项目的所有标签及其 id（内部方法，用于已有连接）
+    fn get_item_tag_refs_internal(&self, conn: &Connection, item_id: i64) -> Result<Vec<TagRef>> {
+        let mut stmt = conn.prepare(
+            "SELECT t.id, t.name FROM tags t
+             JOIN item_tags it ON t.id = it.tag_id
+             WHERE it.item_id = ?1",
+        )?;
+
+        let tags = stmt
+            .query_map(params![item_id], |row| {
+                Ok(TagRef {
+                    id: row.get(0)?,
+                    name: row.get(1)?,
+                })
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(tags)
+    }
+
+    /// 创建尚不存在的标签，返回新建数量
+    pub fn ensure_tags(&self, names: &[String]) -> Result<usize> {
+        let conn = self.conn.lock().unwrap();
+        let tx = conn.unchecked_transaction()?;
+        let mut created = 0;
+
+        for name in names
+            .iter()
+            .map(|name| name.trim())
+            .filter(|name| !name.is_empty())
+        {
+            created += tx.execute(
+                "INSERT OR IGNORE INTO tags (name) VALUES (?1)",
+                params![name],
+            )?;
+        }
+
+        tx.commit()?;
+        Ok(created)
+    }
+
+    /// 获取所有标签
+    pub fn get_all_tags(&self) -> Result<Vec<String>> {
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = conn.prepare("SELECT name FROM tags ORDER BY name")?;
+
+        let tags = stmt
+            .query_map([], |row| row.get(0))?
+            .collect::<Result<Vec<String>, _>>()?;
+
+        Ok(tags)
+    }
+
+    /// 按标签获取项目
+    pub fn get_items_by_tag(&self, tag_name: &str, limit: i64) -> Result<Vec<ClipboardItem>> {
+        self.query_items_by_tag(tag_name, limit, false)
+    }
+
+    /// 获取同时带有该标签且已收藏的项目（按时间从新到旧）
+    pub fn get_favorites_by_tag(&self, tag_name: &str, limit: i64) -> Result<Vec<ClipboardItem>> {
+        self.query_items_by_tag(tag_name, limit, true)
+    }
+
+    fn query_items_by_tag(
+        &self,
+        tag_name: &str,
+        limit: i64,
+        favorites_only: bool,
+    ) -> Result<Vec<ClipboardItem>> {
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = conn.prepare(&format!(
+            "SELECT {ITEM_COLUMNS}
+                 FROM clipboard_history h
+                 JOIN item_tags it ON h.id = it.item_id
+                 JOIN tags t ON it.tag_id = t.id
+                 WHERE t.name = ?1 AND (?3 = 0 OR h.is_favorite = 1)
+                 ORDER BY h.created_at DESC
+                 LIMIT ?2"
+        ))?;
+
+        let items = stmt
+            .query_map(params![tag_name, limit, favorites_only], item_from_row)?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let mut items_with_tags = Vec::new();
+        for mut item in items {
+            item.tags = self.get_item_tags_internal(&conn, item.id)?;
+            items_with_tags.push(item);
+        }
+
+        Ok(items_with_tags)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn memory_db() -> Database {
+        Database::new(PathBuf::from(":memory:")).unwrap()
+    }
+
+    fn add_text(db: &Database, content: &str) -> i64 {
+        db.add_item("text", content, content, None, None, None)
+            .unwrap()
+    }
+
+    #[test]
+    fn find_by_content_matches_type_and_content() {
+        let db = memory_db();
+        let text = add_text(&db, "hello");
+        let file = db
+            .add_item("file", "[\"C:\\\\a.txt\"]", "a.txt", None, None, None)
+            .unwrap();
+
+        assert_eq!(db.find_by_content("text", "hello").unwrap(), Some(text));
+        assert_eq!(
+            db.find_by_content("file", "[\"C:\\\\a.txt\"]").unwrap(),
+            Some(file)
+        );
+        assert_eq!(db.find_by_content("file", "hello").unwrap(), None);
+        assert_eq!(db.find_by_content("text", "hello!").unwrap(), None);
+    }
+
+    fn fts_matches(db: &Database, word: &str) -> Vec<i64> {
+        let conn = db.conn.lock().unwrap();
+        let mut stmt = conn
+            .prepare("SELECT rowid FROM clipboard_fts WHERE clipboard_fts MATCH ?1")
+            .unwrap();
+        let ids = stmt
+            .query_map(params![word], |row| row.get(0))
+            .unwrap()
+            .collect::<rusqlite::Result<Vec<i64>>>()
+            .unwrap();
+        ids
+    }
+
+    #[test]
+    fn edit_replaces_indexed_words() {
+        let db = memory_db();
+        let id = add_text(&db, "fix the typo here");
+
+        db.update_content(id, "fix the spelling here", "fix the spelling here", false)
+            .unwrap();
+
+        assert!(fts_matches(&db, "typo").is_empty());
+        assert_eq!(fts_matches(&db, "spelling"), vec![id]);
+        assert!(db.search_items("typo", 10).unwrap().is_empty());
+        assert_eq!(db.search_items("spelling", 10).unwrap()[0].id, id);
+        assert_eq!(
+            db.find_by_content("text", "fix the spelling here").unwrap(),
+            Some(id)
+        );
+    }
+
+    #[test]
+    fn maintain_limit_reports_trimmed_count() {
+        let db = memory_db();
+        for i in 0..5 {
+            add_text(&db, &format!("item {i}"));
+        }
+
+        assert_eq!(db.maintain_limit(10).unwrap(), 0);
+        assert_eq!(db.maintain_limit(3).unwrap(), 2);
+        assert_eq!(db.count_items().unwrap(), 3);
+    }
+
+    #[test]
+    fn clearing_session_limit_trims_back_to_config_limit() {
+        use crate::config::{effective_history_limit, session_history_limit};
+
+        let db = memory_db();
+        for i in 0..10 {
+            add_text(&db, &format!("item {i}"));
+        }
+        let config_limit = 4;
+
+        // 会话上限高于配置上限时，按会话上限裁剪
+        let session_limit = Some(session_history_limit(7).unwrap());
+        let removed = db
+            .maintain_limit(effective_history_limit(config_limit, session_limit))
+            .unwrap();
+        assert_eq!(removed, 3);
+        assert_eq!(db.count_items().unwrap(), 7);
+
+        // 取消会话上限后回落到配置上限
+        let removed = db
+            .maintain_limit(effective_history_limit(config_limit, None))
+            .unwrap();
+        assert_eq!(removed, 3);
+        assert_eq!(db.count_items().unwrap(), 4);
+        assert_eq!(db.get_items(1, 0).unwrap()[0].content, "item 9");
+    }
+
+    #[test]
+    fn edit_uses_configured_preview() {
+        use crate::clipboard::text_preview;
+        use crate::config::Config;
+
+        let db = memory_db();
+        let config = Config {
+            summarize_json_preview: true,
+            compact_text_preview: true,
+            ..Config::default()
+        };
+
+        let json = add_text(&db, "draft");
+        let content = "{\"name\": \"cat\", \"age\": 3}";
+        db.update_content(json, content, &text_preview(content, &config), false)
+            .unwrap();
+
+        let code = add_text(&db, "draft");
+        let content = "    fn main() {\n        run();\n    }";
+        db.update_content(code, content, &text_preview(content, &config), false)
+            .unwrap();
+
+        let items = db.get_items_by_ids(&[json, code]).unwrap();
+        let preview_of = |id: i64| {
+            items
+                .iter()
+                .find(|item| item.id == id)
+                .map(|item| item.preview.clone())
+                .unwrap()
+        };
+        assert_eq!(preview_of(json), "{…} 2 keys");
+        assert_eq!(preview_of(code), text_preview(content, &config));
+        assert!(!preview_of(code).starts_with(' '));
+    }
+
+    #[test]
+    fn edit_bump_moves_item_to_top() {
+        let db = memory_db();
+        let old = add_text(&db, "older");
+        {
+            let conn = db.conn.lock().unwrap();
+            conn.execute(
+                "UPDATE clipboard_history
+                 SET created_at = '2020-01-01T00:00:00+00:00', created_at_epoch = 1577836800
+                 WHERE id = ?1",
+                params![old],
+            )
+            .unwrap();
+        }
+        add_text(&db, "newer");
+
+        db.update_content(old, "older, edited", "older, edited", true)
+            .unwrap();
+        assert_eq!(db.get_items(10, 0).unwrap()[0].id, old);
+    }
+
+    #[test]
+    fn delete_removes_indexed_words() {
+        let db = memory_db();
+        let id = add_text(&db, "ephemeral note");
+
+        db.delete_item(id).unwrap();
+        assert!(fts_matches(&db, "ephemeral").is_empty());
+    }
+
+    fn item_tags(db: &Database, id: i64) -> Vec<String> {
+        let conn = db.conn.lock().unwrap();
+        let mut tags = db.get_item_tags_internal(&conn, id).unwrap();
+        tags.sort();
+        tags
+    }
+
+    #[test]
+    fn normalize_tag_case_merges_only_case_duplicates() {
+        let db = memory_db();
+        let both = add_text(&db, "both");
+        let upper_only = add_text(&db, "upper only");
+        db.add_item_tag(both, "Work").unwrap();
+        db.add_item_tag(both, "work").unwrap();
+        db.add_item_tag(upper_only, "WORK").unwrap();
+        db.add_item_tag(both, "Rust").unwrap();
+
+        assert_eq!(db.normalize_tag_case(TagCasePolicy::Lower).unwrap(), 2);
+
+        assert_eq!(db.get_all_tags().unwrap(), vec!["Rust", "work"]);
+        assert_eq!(item_tags(&db, both), vec!["Rust", "work"]);
+        assert_eq!(item_tags(&db, upper_only), vec!["work"]);
+    }
+
+    #[test]
+    fn normalize_tag_case_can_keep_first_spelling() {
+        let db = memory_db();
+        let id = add_text(&db, "item");
+        db.add_item_tag(id, "Work").unwrap();
+        db.add_item_tag(id, "work").unwrap();
+
+        assert_eq!(
+            db.normalize_tag_case(TagCasePolicy::PreserveFirst).unwrap(),
+            1
+        );
+        assert_eq!(db.get_all_tags().unwrap(), vec!["Work"]);
+        assert_eq!(item_tags(&db, id), vec!["Work"]);
+        assert_eq!(db.normalize_tag_case(TagCasePolicy::Lower).unwrap(), 0);
+        assert_eq!(db.get_all_tags().unwrap(), vec!["Work"]);
+    }
+
+    fn temp_db_path(name: &str) -> PathBuf {
+        let path =
+            std::env::temp_dir().join(format!("cat-clipboard-{name}-{}.db", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        path
+    }
+
+    fn execute(db: &Database, sql: &str) {
+        db.conn.lock().unwrap().execute_batch(sql).unwrap();
+    }
+
+    #[test]
+    fn adjacent_items_follow_list_order() {
+        let db = memory_db();
+        let oldest = add_text(&db, "oldest");
+        let middle = add_text(&db, "middle");
+        let newest = add_text(&db, "newest");
+        let id_of = |item: Option<ClipboardItem>| item.map(|item| item.id);
+
+        assert_eq!(
+            id_of(db.get_next_item(newest, false).unwrap()),
+            Some(middle)
+        );
+        assert_eq!(
+            id_of(db.get_next_item(middle, false).unwrap()),
+            Some(oldest)
+        );
+        assert_eq!(
+            id_of(db.get_prev_item(oldest, false).unwrap()),
+            Some(middle)
+        );
+
+        assert_eq!(id_of(db.get_next_item(oldest, false).unwrap()), None);
+        assert_eq!(id_of(db.get_prev_item(newest, false).unwrap()), None);
+        assert_eq!(id_of(db.get_next_item(oldest, true).unwrap()), Some(newest));
+        assert_eq!(id_of(db.get_prev_item(newest, true).unwrap()), Some(oldest));
+
+        assert_eq!(id_of(db.get_next_item(9_999, true).unwrap()), None);
+    }
+
+    #[test]
+    fn source_format_round_trips() {
+        let db = memory_db();
+        let captured = db
+            .add_item(
+                "text",
+                "html copy",
+                "html copy",
+                Some("HTML Format"),
+                None,
+                None,
+            )
+            .unwrap();
+        let manual = add_text(&db, "typed by hand");
+
+        let items = db.get_items_by_ids(&[captured, manual]).unwrap();
+        let format_of = |id: i64| {
+            items
+                .iter()
+                .find(|item| item.id == id)
+                .and_then(|item| item.source_format.clone())
+        };
+        assert_eq!(format_of(captured).as_deref(), Some("HTML Format"));
+        assert_eq!(format_of(manual), None);
+
+        let listed = db.get_items(10, 0).unwrap();
+        let listed_format = listed
+            .iter()
+            .find(|item| item.id == captured)
+            .and_then(|item| item.source_format.as_deref());
+        assert_eq!(listed_format, Some("HTML Format"));
+    }
+
+    #[test]
+    fn trash_round_trip_keeps_favorites_and_tags() {
+        let db = memory_db();
+        let favorite = add_text(&db, "keep me");
+        db.toggle_favorite(favorite).unwrap();
+        let tagged = add_text(&db, "tagged");
+        db.add_item_tag(tagged, "work").unwrap();
+        let plain = add_text(&db, "plain");
+
+        db.clear_non_favorites(true).unwrap();
+        assert_eq!(db.count_items().unwrap(), 1);
+        assert_eq!(db.get_items(10, 0).unwrap()[0].id, favorite);
+
+        let trash = db.get_trash(60).unwrap();
+        let mut trashed: Vec<i64> = trash.iter().map(|entry| entry.item.id).collect();
+        trashed.sort();
+        assert_eq!(trashed, vec![tagged, plain]);
+
+        assert_eq!(db.restore_from_trash(&[tagged], 60).unwrap(), 1);
+        assert_eq!(item_tags(&db, tagged), vec!["work"]);
+        assert_eq!(db.search_items("tagged", 10).unwrap()[0].id, tagged);
+        assert_eq!(db.get_trash(60).unwrap().len(), 1);
+
+        assert_eq!(db.restore_from_trash(&[], 60).unwrap(), 1);
+        assert_eq!(db.count_items().unwrap(), 3);
+        assert!(db.get_trash(60).unwrap().is_empty());
+    }
+
+    #[test]
+    fn restore_skips_types_no_longer_allowed() {
+        let db = memory_db();
+        let text = add_text(&db, "text");
+        let image = db
+            .add_item("image", "aW1n", "图片", None, None, None)
+            .unwrap();
+        db.clear_non_favorites(true).unwrap();
+
+        db.set_capture_allowlist(&["text".to_string()]);
+        assert_eq!(db.restore_from_trash(&[], 60).unwrap(), 1);
+        assert_eq!(db.get_items(10, 0).unwrap()[0].id, text);
+        assert_eq!(db.count_items().unwrap(), 1);
+
+        // 被过滤的记录仍留在回收站，允许后可以恢复
+        let trash = db.get_trash(60).unwrap();
+        assert_eq!(trash.len(), 1);
+        assert_eq!(trash[0].item.id, image);
+        db.set_capture_allowlist(&["text".to_string(), "image".to_string()]);
+        assert_eq!(db.restore_from_trash(&[image], 60).unwrap(), 1);
+        assert_eq!(db.count_items().unwrap(), 2);
+    }
+
+    #[test]
+    fn trash_expires_after_retention() {
+        let db = memory_db();
+        add_text(&db, "old");
+        db.clear_non_favorites(true).unwrap();
+        assert_eq!(db.get_trash(60).unwrap().len(), 1);
+
+        execute(
+            &db,
+            "UPDATE clipboard_trash SET deleted_at = '2000-01-01T00:00:00+00:00'",
+        );
+        assert_eq!(db.restore_from_trash(&[], 60).unwrap(), 0);
+        assert!(db.get_trash(60).unwrap().is_empty());
+        assert_eq!(db.count_items().unwrap(), 0);
+    }
+
+    #[test]
+    fn clear_without_trash_deletes_permanently() {
+        let db = memory_db();
+        add_text(&db, "gone");
+
+        db.clear_non_favorites(false).unwrap();
+
+        assert_eq!(db.count_items().unwrap(), 0);
+        assert!(db.get_trash(60).unwrap().is_empty());
+    }
+
+    #[test]
+    fn audit_log_follows_enabled_flag() {
+        let db = memory_db();
+        let silent = add_text(&db, "not audited");
+        db.delete_item(silent).unwrap();
+        assert!(db.get_audit_log(10, 0).unwrap().is_empty());
+
+        db.set_audit_enabled(true);
+        let id = add_text(&db, "audited");
+        db.delete_item(id).unwrap();
+
+        let entries = db.get_audit_log(10, 0).unwrap();
+        let ops: Vec<&str> = entries.iter().map(|entry| entry.op.as_str()).collect();
+        assert_eq!(ops, vec!["delete", "add"]);
+        assert!(entries.iter().all(|entry| entry.item_id == Some(id)));
+
+        db.set_audit_enabled(false);
+        add_text(&db, "quiet again");
+        assert_eq!(db.get_audit_log(10, 0).unwrap().len(), 2);
+    }
+
+    #[test]
+    fn maintenance_trims_expired_audit_entries() {
+        let db = memory_db();
+        db.set_audit_enabled(true);
+        let old = add_text(&db, "old");
+        add_text(&db, "recent");
+        execute(
+            &db,
+            &format!(
+                "UPDATE audit_log SET created_at = '2000-01-01T00:00:00+00:00' WHERE item_id = {old}"
+            ),
+        );
+
+        let summary = db
+            .run_maintenance(MaintenanceOptions {
+                retention_days: 0,
+                trash_retention_minutes: 60,
+                audit_retention_days: 30,
+            })
+            .unwrap();
+
+        assert_eq!(summary.audit_trimmed, 1);
+        assert_eq!(summary.pruned, 0);
+        let remaining = db.get_audit_log(10, 0).unwrap();
+        assert_eq!(remaining.len(), 1);
+        assert_ne!(remaining[0].item_id, Some(old));
+    }
+
+    fn imported(content_type: &str, content: &str, created_at: &str) -> ImportedEntry {
+        ImportedEntry {
+            content_type: content_type.to_string(),
+            content: content.to_string(),
+            preview: content.to_string(),
+            created_at: created_at.to_string(),
+            is_favorite: false,
+            thumbnail: None,
+        }
+    }
+
+    #[test]
+    fn capture_allowlist_applies_to_every_insert_path() {
+        let db = memory_db();
+        db.set_capture_allowlist(&["image".to_string()]);
+
+        assert!(db.add_item("text", "a", "a", None, None, None).is_err());
+        assert!(db
+            .insert_text_lines(vec![Ok("line".to_string())], false)
+            .is_err());
+
+        let counts = db
+            .insert_imported_items(&[
+                imported("text", "b", "2024-01-01T00:00:00+00:00"),
+                imported("image", "AAAA", "2024-01-01T00:00:00+00:00"),
+            ])
+            .unwrap();
+        assert_eq!(
+            counts,
+            ImportedCounts {
+                inserted: 1,
+                duplicates: 0,
+                disallowed: 1,
+            }
+        );
+        assert_eq!(db.count_items().unwrap(), 1);
+
+        db.set_capture_allowlist(&["text".to_string(), "image".to_string()]);
+        assert!(db.add_item("text", "a", "a", None, None, None).is_ok());
+    }
+
+    #[test]
+    fn reimport_skips_entries_with_same_time_and_content() {
+        let db = memory_db();
+        let entries = vec![
+            imported("text", "first", "2024-01-01T08:00:00+00:00"),
+            imported("text", "second", "2024-01-01T09:00:00+00:00"),
+        ];
+
+        let counts = db.insert_imported_items(&entries).unwrap();
+        assert_eq!((counts.inserted, counts.duplicates), (2, 0));
+
+        let again = db
+            .insert_imported_items(&[
+                imported("text", "first", "2024-01-01T08:00:00+00:00"),
+                // 同一时间点换算为其他时区，仍视为重复
+                imported("text", "second", "2024-01-01T17:00:00+08:00"),
+                // 内容相同但时间不同，是一次新的复制
+                imported("text", "first", "2024-01-02T08:00:00+00:00"),
+            ])
+            .unwrap();
+        assert_eq!((again.inserted, again.duplicates), (1, 2));
+        assert_eq!(db.count_items().unwrap(), 3);
+    }
+
+    #[test]
+    fn largest_items_order_by_utf8_bytes() {
+        let db = memory_db();
+        let ascii = add_text(&db, "abcdefgh");
+        let wide = add_text(&db, "猫猫猫");
+        let short = add_text(&db, "ab");
+
+        let largest = db.get_largest_items(10).unwrap();
+        let order: Vec<(i64, i64)> = largest
+            .iter()
+            .map(|item| (item.id, item.byte_size))
+            .collect();
+        assert_eq!(order, vec![(wide, 9), (ascii, 8), (short, 2)]);
+
+        assert_eq!(db.get_largest_items(1).unwrap()[0].id, wide);
+    }
+
+    #[test]
+    fn touch_moves_item_to_top() {
+        let db = memory_db();
+        let first = add_text(&db, "first");
+        let second = add_text(&db, "second");
+        execute(
+            &db,
+            "UPDATE clipboard_history SET created_at = '2020-01-01T00:00:00+00:00',
+                                          created_at_epoch = 1577836800",
+        );
+
+        db.touch_item(first).unwrap();
+
+        let ids: Vec<i64> = db
+            .get_items(10, 0)
+            .unwrap()
+            .iter()
+            .map(|item| item.id)
+            .collect();
+        assert_eq!(ids, vec![first, second]);
+        assert!(db.touch_item(9_999).is_err());
+    }
+
+    #[test]
+    fn text_lines_skip_blanks_and_strip_crlf() {
+        let db = memory_db();
+        let lines = ["alpha\r", "\r", "   ", "beta\r", "alpha"].map(|line| Ok(line.to_string()));
+
+        assert_eq!(db.insert_text_lines(lines, false).unwrap(), 3);
+        let mut contents: Vec<String> = db
+            .get_items(10, 0)
+            .unwrap()
+            .into_iter()
+            .map(|item| item.content)
+            .collect();
+        contents.sort();
+        assert_eq!(contents, vec!["alpha", "alpha", "beta"]);
+    }
+
+    #[test]
+    fn text_lines_skip_duplicates_when_requested() {
+        let db = memory_db();
+        add_text(&db, "existing");
+        let lines = ["existing", "new", "new", "other"].map(|line| Ok(line.to_string()));
+
+        assert_eq!(db.insert_text_lines(lines, true).unwrap(), 2);
+        assert_eq!(db.count_items().unwrap(), 3);
+    }
+
+    #[test]
+    fn untagged_items_exclude_tagged_ones() {
+        let db = memory_db();
+        let tagged = add_text(&db, "tagged");
+        db.add_item_tag(tagged, "work").unwrap();
+        let untagged = add_text(&db, "untagged");
+        let untagged_again = add_text(&db, "cleared");
+        db.add_item_tag(untagged_again, "tmp").unwrap();
+        db.remove_item_tag(untagged_again, "tmp").unwrap();
+
+        let mut ids: Vec<i64> = db
+            .get_untagged_items(10, 0)
+            .unwrap()
+            .iter()
+            .map(|item| item.id)
+            .collect();
+        ids.sort();
+        assert_eq!(ids, vec![untagged, untagged_again]);
+
+        assert_eq!(db.get_untagged_items(1, 0).unwrap().len(), 1);
+        assert_eq!(db.get_untagged_items(10, 2).unwrap().len(), 0);
+    }
+
+    #[test]
+    fn fold_search_text_normalizes_case_width_and_accents() {
+        assert_eq!(fold_search_text("Crème Brûlée"), "creme brulee");
+        assert_eq!(fold_search_text("ＡＢＣ１２３"), "abc123");
+        assert_eq!(fold_search_text("ÉCOLE"), "ecole");
+        assert_eq!(fold_search_text("猫"), "猫");
+    }
+
+    /// 模拟升级前写入、尚未补齐派生列的记录
+    fn clear_derived_columns(db: &Database, ids: &[i64]) {
+        let ids = ids
+            .iter()
+            .map(|id| id.to_string())
+            .collect::<Vec<_>>()
+            .join(",");
+        execute(
+            db,
+            &format!(
+                "UPDATE clipboard_history
+                 SET content_hash = NULL, created_at_epoch = NULL, search_text = NULL
+                 WHERE id IN ({ids})"
+            ),
+        );
+    }
+
+    #[test]
+    fn search_uses_folded_text_and_falls_back_when_null() {
+        let db = memory_db();
+        let folded = add_text(&db, "Crème Brûlée recipe");
+        let legacy = add_text(&db, "Legacy Note");
+        clear_derived_columns(&db, &[legacy]);
+
+        for query in ["creme", "BRULEE", "ｒｅｃｉｐｅ"] {
+            let ids: Vec<i64> = db
+                .search_items(query, 10)
+                .unwrap()
+                .iter()
+                .map(|item| item.id)
+                .collect();
+            assert_eq!(ids, vec![folded], "query {query}");
+        }
+
+        // 搜索文本尚未补齐时按原文不区分大小写匹配
+        assert_eq!(db.search_items("legacy note", 10).unwrap()[0].id, legacy);
+    }
+
+    #[test]
+    fn backup_and_restore_round_trip() {
+        let db = memory_db();
+        let saved = add_text(&db, "saved before backup");
+        db.add_item_tag(saved, "work").unwrap();
+        let path = temp_db_path("backup-round-trip");
+        db.backup_to(&path).unwrap();
+
+        add_text(&db, "added after backup");
+        db.restore_from(&path).unwrap();
+
+        let items = db.get_items(10, 0).unwrap();
+        assert_eq!(items.len(), 1);
+        assert_eq!(items[0].content, "saved before backup");
+        assert_eq!(item_tags(&db, saved), vec!["work"]);
+        assert_eq!(db.search_items("backup", 10).unwrap()[0].id, saved);
+
+        let _ = std::fs::remove_file(path);
+    }
+
+    #[test]
+    fn restore_rejects_invalid_backups() {
+        let db = memory_db();
+        add_text(&db, "current");
+
+        let not_sqlite = temp_db_path("backup-not-sqlite");
+        std::fs::write(&not_sqlite, "definitely not a database").unwrap();
+        assert!(db.restore_from(&not_sqlite).is_err());
+
+        let wrong_schema = temp_db_path("backup-wrong-schema");
+        Connection::open(&wrong_schema)
+            .unwrap()
+            .execute_batch("CREATE TABLE clipboard_history (id INTEGER PRIMARY KEY);")
+            .unwrap();
+        assert!(db.restore_from(&wrong_schema).is_err());
+
+        // 校验失败时当前数据不受影响
+        assert_eq!(db.get_items(10, 0).unwrap()[0].content, "current");
+
+        let _ = std::fs::remove_file(not_sqlite);
+        let _ = std::fs::remove_file(wrong_schema);
+    }
+
+    #[test]
+    fn ranking_weights_change_order() {
+        let db = memory_db();
+        let used = add_text(&db, "copied often");
+        let favorite = add_text(&db, "favorite");
+        let recent = add_text(&db, "fresh");
+        db.toggle_favorite(favorite).unwrap();
+        for _ in 0..3 {
+            db.record_copy(used).unwrap();
+        }
+        execute(
+            &db,
+            &format!(
+                "UPDATE clipboard_history
+                 SET created_at_epoch = CAST(strftime('%s', 'now') AS INTEGER) - 10 * 86400
+                 WHERE id IN ({used}, {favorite})"
+            ),
+        );
+
+        let top = |recency: f64, usage: f64, favorite: f64| {
+            db.get_ranked(
+                1,
+                RankingWeights {
+                    recency,
+                    usage,
+                    favorite,
+                },
+            )
+            .unwrap()[0]
+                .id
+        };
+
+        assert_eq!(top(1.0, 0.0, 0.0), recent);
+        assert_eq!(top(0.0, 1.0, 0.0), used);
+        assert_eq!(top(0.0, 0.0, 1.0), favorite);
+        assert_eq!(top(10.0, 1.0, 1.0), recent);
+    }
+
+    #[test]
+    fn image_prune_reports_bytes_and_keeps_favorites() {
+        let db = memory_db();
+        let old_image = db
+            .add_item("image", "aGVsbG8=", "图片", None, None, Some("dGh1bWI="))
+            .unwrap();
+        let favorite_image = db
+            .add_item("image", "ZmF2b3JpdGU=", "图片", None, None, None)
+            .unwrap();
+        db.toggle_favorite(favorite_image).unwrap();
+        let new_image = db
+            .add_item("image", "bmV3", "图片", None, None, None)
+            .unwrap();
+        let old_text = add_text(&db, "old text");
+        execute(
+            &db,
+            &format!(
+                "UPDATE clipboard_history
+                 SET created_at = '2000-01-01T00:00:00+00:00', created_at_epoch = 946684800
+                 WHERE id IN ({old_image}, {favorite_image}, {old_text})"
+            ),
+        );
+
+        let report = db.prune_images_older_than(30).unwrap();
+
+        assert_eq!(report.removed, 1);
+        assert_eq!(
+            report.bytes_reclaimed,
+            ("aGVsbG8=".len() + "dGh1bWI=".len()) as i64
+        );
+        let mut remaining: Vec<i64> = db
+            .get_items(10, 0)
+            .unwrap()
+            .iter()
+            .map(|item| item.id)
+            .collect();
+        remaining.sort();
+        assert_eq!(remaining, vec![favorite_image, new_image, old_text]);
+
+        assert_eq!(db.prune_images_older_than(0).unwrap().removed, 0);
+    }
+
+    #[test]
+    fn exported_tags_import_into_fresh_database() {
+        let source = memory_db();
+        let id = add_text(&source, "tagged");
+        source.add_item_tag(id, "work").unwrap();
+        source.add_tag("unused").unwrap();
+        let exported = source.get_all_tags().unwrap();
+
+        let target = memory_db();
+        target.add_tag("work").unwrap();
+        let mut names = exported.clone();
+        names.push("  ".to_string());
+
+        assert_eq!(target.ensure_tags(&names).unwrap(), 1);
+        assert_eq!(target.get_all_tags().unwrap(), exported);
+        assert_eq!(target.ensure_tags(&exported).unwrap(), 0);
+    }
+
+    #[test]
+    fn config_reset_keeps_history() {
+        use crate::config::{restore_default_config, Config};
+        use std::sync::Mutex;
+
+        let db = memory_db();
+        let favorite = add_text(&db, "favorite");
+        db.toggle_favorite(favorite).unwrap();
+        db.add_item_tag(favorite, "work").unwrap();
+        add_text(&db, "plain");
+
+        let config_path = temp_db_path("config-reset").with_extension("json");
+        let custom = Config {
+            max_history_items: 10,
+            capture_allowlist: vec!["text".to_string()],
+            enable_audit_log: true,
+            hotkey: "Alt+Shift+H".to_string(),
+            ..Config::default()
+        };
+        custom.save(config_path.clone()).unwrap();
+        db.set_capture_allowlist(&custom.capture_allowlist);
+        db.set_audit_enabled(custom.enable_audit_log);
+        let config = Mutex::new(custom);
+
+        let defaults = restore_default_config(&db, &config, config_path.clone()).unwrap();
+        assert_eq!(
+            defaults.max_history_items,
+            Config::default().max_history_items
+        );
+        assert_eq!(
+            config.lock().unwrap().capture_allowlist,
+            defaults.capture_allowlist
+        );
+
+        assert_eq!(
+            db.audit_enabled.load(Ordering::Relaxed),
+            defaults.enable_audit_log
+        );
+
+        // 快捷键恢复为默认组合，前端收到的配置据此重新注册
+        assert_eq!(defaults.hotkey, Config::default().hotkey);
+        assert_eq!(config.lock().unwrap().hotkey, defaults.hotkey);
+
+        let reloaded = Config::load(config_path.clone()).unwrap();
+        assert_eq!(reloaded.max_history_items, defaults.max_history_items);
+        assert_eq!(reloaded.hotkey, defaults.hotkey);
+        assert_eq!(reloaded.capture_allowlist, defaults.capture_allowlist);
+
+        assert_eq!(db.count_items().unwrap(), 2);
+        assert_eq!(item_tags(&db, favorite), vec!["work"]);
+        assert!(db.get_items_by_ids(&[favorite]).unwrap()[0].is_favorite);
+        // 默认允许列表已同步到数据库，图片可以再次写入
+        assert!(db
+            .add_item("image", "aW1n", "图片", None, None, None)
+            .is_ok());
+
+        let _ = std::fs::remove_file(config_path);
+    }
+
+    #[test]
+    fn histogram_offset_shifts_hour_buckets() {
+        let db = memory_db();
+        let before_midnight = add_text(&db, "23:30 UTC");
+        let after_midnight = add_text(&db, "01:15 UTC");
+        // 2024-01-01 23:30 UTC 与 2024-01-02 01:15 UTC
+        execute(
+            &db,
+            &format!(
+                "UPDATE clipboard_history SET created_at_epoch = 1704151800 WHERE id = {before_midnight};
+                 UPDATE clipboard_history SET created_at_epoch = 1704158100 WHERE id = {after_midnight};"
+            ),
+        );
+
+        let utc = db.get_hourly_histogram(0).unwrap();
+        assert_eq!((utc[23], utc[1]), (1, 1));
+
+        // UTC+8：07:30 与 09:15
+        let east = db.get_hourly_histogram(8 * 60).unwrap();
+        assert_eq!((east[7], east[9]), (1, 1));
+
+        // UTC-5:30：跨日回到前一天的 18:00 与 19:45
+        let west = db.get_hourly_histogram(-(5 * 60 + 30)).unwrap();
+        assert_eq!((west[18], west[19]), (1, 1));
+        assert_eq!(west.iter().sum::<i64>(), 2);
+    }
+
+    #[test]
+    fn item_size_counts_utf8_bytes() {
+        let db = memory_db();
+        let ascii = add_text(&db, "hello");
+        let wide = add_text(&db, "猫咪");
+
+        assert_eq!(db.get_item_size(ascii).unwrap(), 5);
+        assert_eq!(db.get_item_size(wide).unwrap(), 6);
+        let err = db.get_item_size(9_999).unwrap_err();
+        assert!(err.to_string().contains("9999"));
+    }
+
+    #[test]
+    fn favorites_by_tag_require_both() {
+        let db = memory_db();
+        let favorite_tagged = add_text(&db, "favorite tagged");
+        db.toggle_favorite(favorite_tagged).unwrap();
+        db.add_item_tag(favorite_tagged, "work").unwrap();
+        let tagged = add_text(&db, "tagged only");
+        db.add_item_tag(tagged, "work").unwrap();
+        let favorite_other = add_text(&db, "favorite other tag");
+        db.toggle_favorite(favorite_other).unwrap();
+        db.add_item_tag(favorite_other, "home").unwrap();
+
+        let favorites = db.get_favorites_by_tag("work", 10).unwrap();
+        assert_eq!(favorites.len(), 1);
+        assert_eq!(favorites[0].id, favorite_tagged);
+        assert_eq!(favorites[0].tags, vec!["work"]);
+
+        assert_eq!(db.get_items_by_tag("work", 10).unwrap().len(), 2);
+        assert!(db.get_favorites_by_tag("missing", 10).unwrap().is_empty());
+    }
+
+    #[test]
+    fn items_with_tag_ids_carry_ids_and_names() {
+        let db = memory_db();
+        let work = db.add_tag("work").unwrap();
+        let id = add_text(&db, "tagged");
+        db.add_item_tag(id, "work").unwrap();
+        add_text(&db, "untagged");
+
+        let items = db.get_items_with_tag_ids(10, 0).unwrap();
+        let tagged = items.iter().find(|entry| entry.item.id == id).unwrap();
+        assert_eq!(
+            tagged.tag_refs,
+            vec![TagRef {
+                id: work,
+                name: "work".to_string()
+            }]
+        );
+        assert_eq!(tagged.item.tags, vec!["work"]);
+        assert!(items
+            .iter()
+            .filter(|entry| entry.item.id != id)
+            .all(|entry| entry.tag_refs.is_empty()));
+
+        // 序列化时记录字段被展开，前端仍能读取 `tags`
+        let json = serde_json::to_value(tagged).unwrap();
+        assert_eq!(json["id"], id);
+        assert_eq!(json["tags"][0], "work");
+        assert_eq!(json["tag_refs"][0]["id"], work);
+    }
+
+    #[test]
+    fn history_filter_parse_and_apply() {
+        assert_eq!(HistoryFilter::parse(" all ").unwrap(), HistoryFilter::All);
+        assert_eq!(
+            HistoryFilter::parse("type: image").unwrap(),
+            HistoryFilter::ContentType("image".to_string())
+        );
+        for invalid in ["type:video", "tag:", "everything", "color:red"] {
+            assert!(HistoryFilter::parse(invalid).is_err(), "{invalid}");
+        }
+
+        let db = memory_db();
+        let text = add_text(&db, "text");
+        db.add_item_tag(text, "work").unwrap();
+        let image = db
+            .add_item("image", "aW1n", "图片", None, None, None)
+            .unwrap();
+        db.toggle_favorite(image).unwrap();
+
+        let ids = |spec: &str| {
+            let filter = HistoryFilter::parse(spec).unwrap();
+            let mut ids: Vec<i64> = db
+                .get_filtered(&filter, 10)
+                .unwrap()
+                .iter()
+                .map(|item| item.id)
+                .collect();
+            ids.sort();
+            ids
+        };
+
+        assert_eq!(ids("all"), vec![text, image]);
+        assert_eq!(ids("favorites"), vec![image]);
+        assert_eq!(ids("untagged"), vec![image]);
+        assert_eq!(ids("type:text"), vec![text]);
+        assert_eq!(ids("tag:work"), vec![text]);
+        assert!(ids("favorite-tag:work").is_empty());
+    }
+
+    #[test]
+    fn repair_removes_dangling_associations() {
+        let db = memory_db();
+        let id = add_text(&db, "tagged");
+        db.add_item_tag(id, "work").unwrap();
+        let work = db.add_tag("work").unwrap();
+        execute(
+            &db,
+            &format!(
+                "PRAGMA foreign_keys = OFF;
+                 INSERT INTO item_tags (item_id, tag_id) VALUES (9999, {work});
+                 INSERT INTO item_tags (item_id, tag_id) VALUES ({id}, 8888);"
+            ),
+        );
+
+        assert_eq!(db.count_orphan_associations().unwrap(), 2);
+        assert_eq!(db.repair_orphan_associations().unwrap(), 2);
+        assert_eq!(db.count_orphan_associations().unwrap(), 0);
+        assert_eq!(item_tags(&db, id), vec!["work"]);
+
+        let foreign_keys: i64 = db
+            .conn
+            .lock()
+            .unwrap()
+            .query_row("PRAGMA foreign_keys", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(foreign_keys, 1);
+    }
+
+    #[test]
+    fn source_title_is_searchable() {
+        let db = memory_db();
+        let id = db
+            .add_item(
+                "text",
+                "body",
+                "body",
+                None,
+                Some("Quarterly Report - Excel"),
+                None,
+            )
+            .unwrap();
+        add_text(&db, "unrelated");
+
+        let found = db.search_items("quarterly", 10).unwrap();
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].id, id);
+        assert_eq!(
+            found[0].source_title.as_deref(),
+            Some("Quarterly Report - Excel")
+        );
+    }
+
+    #[test]
+    fn exit_clear_skips_trash() {
+        let db = memory_db();
+        let favorite = add_text(&db, "favorite");
+        db.toggle_favorite(favorite).unwrap();
+        add_text(&db, "plain");
+        add_text(&db, "trashed earlier");
+        db.clear_non_favorites(true).unwrap();
+        add_text(&db, "plain again");
+
+        db.clear_for_exit(ExitClearAction::NonFavorites).unwrap();
+        assert_eq!(db.count_items().unwrap(), 1);
+        assert_eq!(db.get_trash(60).unwrap().len(), 2);
+
+        db.clear_for_exit(ExitClearAction::All).unwrap();
+        assert_eq!(db.count_items().unwrap(), 0);
+        assert!(db.get_trash(60).unwrap().is_empty());
+    }
+
+    #[test]
+    fn regex_search_matches_and_rejects_invalid_patterns() {
+        let db = memory_db();
+        let order = add_text(&db, "order #1234");
+        add_text(&db, "no digits here");
+        db.add_item("image", "MTIzNA==", "图片", None, None, None)
+            .unwrap();
+
+        let found = db.search_regex(r"#\d{4}", 10).unwrap();
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].id, order);
+        assert!(db.search_regex(r"\d", 0).unwrap().is_empty());
+
+        let err = db.search_regex("(unclosed", 10).unwrap_err();
+        assert!(err.to_string().contains("Invalid regular expression"));
+    }
+
+    #[test]
+    fn regex_search_respects_scan_limit_and_budget() {
+        let db = memory_db();
+        let ids: Vec<i64> = (0..3)
+            .map(|n| add_text(&db, &format!("match {n}")))
+            .collect();
+        let budget = std::time::Duration::from_secs(60);
+
+        let mut scanned: Vec<i64> = db
+            .search_regex_within("match", 10, 2, budget)
+            .unwrap()
+            .iter()
+            .map(|item| item.id)
+            .collect();
+        scanned.sort();
+        assert_eq!(scanned, ids[1..]);
+
+        assert_eq!(
+            db.search_regex_within("match", 2, 100, budget)
+                .unwrap()
+                .len(),
+            2
+        );
+
+        // 预算耗尽后立即返回已找到的结果：零预算只扫描最新的一行
+        let hurried = db
+            .search_regex_within("match", 10, 100, std::time::Duration::ZERO)
+            .unwrap();
+        assert_eq!(hurried.len(), 1);
+        assert_eq!(hurried[0].id, ids[2]);
+    }
+
+    fn add_files(db: &Database, paths: &[&str]) -> i64 {
+        let content = serde_json::to_string(paths).unwrap();
+        db.add_item("file", &content, paths[0], None, None, None)
+            .unwrap()
+    }
+
+    #[test]
+    fn referencing_path_matches_whole_paths_only() {
+        let db = memory_db();
+        let exact = add_files(&db, &["C:\\foo", "C:\\other.txt"]);
+        let prefixed = add_files(&db, &["C:\\foobar"]);
+        let nested = add_files(&db, &["C:\\foo\\inner.txt"]);
+        let slashes = add_files(&db, &["C:/foo/"]);
+        add_text(&db, "C:\\foo");
+
+        let mut ids: Vec<i64> = db
+            .find_items_referencing_path("C:\\foo")
+            .unwrap()
+            .iter()
+            .map(|item| item.id)
+            .collect();
+        ids.sort();
+
+        assert_eq!(ids, vec![exact, slashes]);
+        assert!(!ids.contains(&prefixed));
+        assert!(!ids.contains(&nested));
+        assert!(db.find_items_referencing_path("  ").unwrap().is_empty());
+    }
+
+    #[test]
+    fn normalize_path_unifies_separators() {
+        assert_eq!(normalize_path_for_match("C:\\Docs\\", false), "C:/Docs");
+        assert_eq!(
+            normalize_path_for_match("C:\\Docs\\A.txt", true),
+            "c:/docs/a.txt"
+        );
+        assert_eq!(normalize_path_for_match("/", false), "/");
+    }
+
+    #[test]
+    fn backfill_resumes_from_saved_cursor() {
+        let path = temp_db_path("backfill");
+        let ids: Vec<i64> = {
+            let db = Database::new(path.clone()).unwrap();
+            let ids = (0..5)
+                .map(|index| add_text(&db, &format!("Café {index}")))
+                .collect::<Vec<_>>();
+            clear_derived_columns(&db, &[ids[0], ids[2], ids[4]]);
+
+            let first = db.backfill_batch(2).unwrap();
+            assert_eq!(
+                first,
+                BackfillProgress {
+                    processed: 2,
+                    remaining: 1,
+                    done: false,
+                }
+            );
+            assert!(!db.is_backfill_done().unwrap());
+            ids
+        };
+
+        // 重新打开后从游标继续，不再重复处理已补齐的记录
+        let db = Database::new(path.clone()).unwrap();
+        let second = db.backfill_batch(2).unwrap();
+        assert_eq!((second.processed, second.done), (1, true));
+        assert!(db.is_backfill_done().unwrap());
+        assert_eq!(db.find_by_content("text", "Café 4").unwrap(), Some(ids[4]));
+        assert_eq!(db.search_items("cafe 2", 10).unwrap()[0].id, ids[2]);
+
+        drop(db);
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[test]
+    fn lookups_fall_back_before_backfill() {
+        let db = memory_db();
+        let text = add_text(&db, "legacy text");
+        let image = db
+            .add_item("image", "AAAA", "img", None, None, None)
+            .unwrap();
+        execute(
+            &db,
+            "UPDATE clipboard_history SET created_at = '2020-01-01T00:00:00+00:00'",
+        );
+        clear_derived_columns(&db, &[text, image]);
+
+        assert_eq!(
+            db.find_by_content("text", "legacy text").unwrap(),
+            Some(text)
+        );
+        assert_eq!(
+            db.insert_text_lines(vec![Ok("legacy text".to_string())], true)
+                .unwrap(),
+            0
+        );
+
+        let counts = db
+            .insert_imported_items(&[imported("text", "legacy text", "2020-01-01T00:00:00+00:00")])
+            .unwrap();
+        assert_eq!(counts.duplicates, 1);
+
+        assert_eq!(db.prune_images_older_than(30).unwrap().removed, 1);
+        assert_eq!(db.get_item_size(image).ok(), None);
+    }
+
+    #[test]
+    fn delete_tag_reassigns_and_merges() {
+        let db = memory_db();
+        let only_old = add_text(&db, "only old");
+        let has_both = add_text(&db, "has both");
+        db.add_item_tag(only_old, "draft").unwrap();
+        db.add_item_tag(has_both, "draft").unwrap();
+        db.add_item_tag(has_both, "todo").unwrap();
+
+        db.delete_tag("draft", Some("todo")).unwrap();
+
+        assert_eq!(db.get_all_tags().unwrap(), vec!["todo"]);
+        assert_eq!(item_tags(&db, only_old), vec!["todo"]);
+        assert_eq!(item_tags(&db, has_both), vec!["todo"]);
+    }
+
+    #[test]
+    fn delete_tag_without_target_drops_associations() {
+        let db = memory_db();
+        let id = add_text(&db, "item");
+        db.add_item_tag(id, "draft").unwrap();
+
+        db.delete_tag("draft", None).unwrap();
+
+        assert!(db.get_all_tags().unwrap().is_empty());
+        assert!(item_tags(&db, id).is_empty());
+        assert_eq!(db.count_items().unwrap(), 1);
+    }
+
+    #[test]
+    fn delete_tag_rejects_invalid_targets() {
+        let db = memory_db();
+        let id = add_text(&db, "item");
+        db.add_item_tag(id, "draft").unwrap();
+
+        assert!(db.delete_tag("draft", Some("draft")).is_err());
+        assert!(db.delete_tag("draft", Some(" draft ")).is_err());
+        assert!(db.delete_tag("draft", Some("  ")).is_err());
+        assert!(db.delete_tag("missing", None).is_err());
+        assert_eq!(item_tags(&db, id), vec!["draft"]);
+    }
 }
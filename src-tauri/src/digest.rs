@@ -0,0 +1,138 @@
+use anyhow::{anyhow, Result};
+use chrono::DateTime;
+
+use crate::database::ClipboardItem;
+
+/// 摘要的排版方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DigestFormat {
+    Numbered,
+    Bulleted,
+    Plain,
+}
+
+impl DigestFormat {
+    pub fn parse(name: &str) -> Result<Self> {
+        match name.trim() {
+            "numbered" => Ok(Self::Numbered),
+            "bulleted" => Ok(Self::Bulleted),
+            "plain" => Ok(Self::Plain),
+            other => Err(anyhow!("Unsupported digest format: {other}")),
+        }
+    }
+}
+
+/// 将记录的预览按顺序拼成便于粘贴分享的纯文本摘要。
+/// `include_meta` 为 true 时在每条下方附上标签与时间。
+pub fn build_digest(items: &[ClipboardItem], format: DigestFormat, include_meta: bool) -> String {
+    let blocks: Vec<String> = items
+        .iter()
+        .enumerate()
+        .map(|(index, item)| {
+            let marker = match format {
+                DigestFormat::Numbered => format!("{}. ", index + 1),
+                DigestFormat::Bulleted => "- ".to_string(),
+                DigestFormat::Plain => String::new(),
+            };
+            let indent = " ".repeat(marker.chars().count());
+
+            let mut lines: Vec<String> = item
+                .preview
+                .lines()
+                .enumerate()
+                .map(|(line_index, line)| {
+                    if line_index == 0 {
+                        format!("{marker}{line}")
+                    } else {
+                        format!("{indent}{line}")
+                    }
+                })
+                .collect();
+            if lines.is_empty() {
+                lines.push(marker.trim_end().to_string());
+            }
+
+            if include_meta {
+                lines.push(format!("{indent}{}", digest_meta(item)));
+            }
+
+            lines.join("\n")
+        })
+        .collect();
+
+    let separator = match format {
+        DigestFormat::Plain => "\n\n",
+        _ => "\n",
+    };
+    blocks.join(separator)
+}
+
+fn digest_meta(item: &ClipboardItem) -> String {
+    let timestamp = DateTime::parse_from_rfc3339(&item.created_at)
+        .map(|parsed| parsed.format("%Y-%m-%d %H:%M").to_string())
+        .unwrap_or_else(|_| item.created_at.clone());
+
+    if item.tags.is_empty() {
+        format!("({timestamp})")
+    } else {
+        let tags: Vec<String> = item.tags.iter().map(|tag| format!("#{tag}")).collect();
+        format!("({} · {timestamp})", tags.join(" "))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn item(preview: &str, tags: &[&str]) -> ClipboardItem {
+        ClipboardItem {
+            id: 1,
+            content_type: "text".into(),
+            content: preview.into(),
+            preview: preview.into(),
+            is_favorite: false,
+            tags: tags.iter().map(|tag| tag.to_string()).collect(),
+            created_at: "2024-03-05T14:07:00+00:00".into(),
+            source_format: None,
+            thumbnail: None,
+            source_title: None,
+        }
+    }
+
+    #[test]
+    fn formats_markers_and_continuation_lines() {
+        let items = [item("first\nsecond line", &[]), item("other", &[])];
+
+        assert_eq!(
+            build_digest(&items, DigestFormat::Numbered, false),
+            "1. first\n   second line\n2. other"
+        );
+        assert_eq!(
+            build_digest(&items, DigestFormat::Bulleted, false),
+            "- first\n  second line\n- other"
+        );
+        assert_eq!(
+            build_digest(&items, DigestFormat::Plain, false),
+            "first\nsecond line\n\nother"
+        );
+    }
+
+    #[test]
+    fn include_meta_appends_tags_and_time() {
+        let items = [item("tagged", &["work", "todo"]), item("", &[])];
+
+        assert_eq!(
+            build_digest(&items, DigestFormat::Bulleted, true),
+            "- tagged\n  (#work #todo · 2024-03-05 14:07)\n-\n  (2024-03-05 14:07)"
+        );
+    }
+
+    #[test]
+    fn parse_rejects_unknown_format() {
+        assert_eq!(
+            DigestFormat::parse(" numbered ").unwrap(),
+            DigestFormat::Numbered
+        );
+        assert!(DigestFormat::parse("markdown").is_err());
+    }
+}
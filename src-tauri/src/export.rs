@@ -0,0 +1,314 @@
+use anyhow::{anyhow, Result};
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine as _};
+use chrono::Utc;
+use serde::Serialize;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::database::{content_hash, ClipboardItem, Database};
+
+/// 文件名中取自预览的部分最多保留的字符数
+const MAX_STEM_CHARS: usize = 48;
+
+/// 脱敏记录中哈希保留的十六进制位数
+const REDACTED_HASH_CHARS: usize = 16;
+
+/// 脱敏导出时每次从数据库读取的记录数，避免一次性载入全部内容（图片可能有数 MB）
+const REDACT_PAGE_SIZE: i64 = 500;
+
+/// 脱敏后的记录：内容与预览只保留字节数和加盐哈希，
+/// 类型、时间、标签与收藏状态原样保留，来源窗口标题与缩略图不导出
+#[derive(Debug, Clone, Serialize)]
+pub struct RedactedItem {
+    pub id: i64,
+    pub content_type: String,
+    pub content_length: usize,
+    pub content_hash: String,
+    pub preview_length: usize,
+    pub preview_hash: String,
+    pub is_favorite: bool,
+    pub tags: Vec<String>,
+    pub created_at: String,
+    pub source_format: Option<String>,
+}
+
+/// 用于问题报告的脱敏导出文件
+#[derive(Debug, Clone, Serialize)]
+pub struct RedactedExport {
+    pub exported_at: String,
+    pub item_count: usize,
+    pub items: Vec<RedactedItem>,
+}
+
+/// 加盐哈希：同一次导出中相同内容的哈希一致，便于复现去重问题，
+/// 又不能通过对常见短文本（如密码）求哈希反查原文
+fn salted_hash(salt: &str, value: &str) -> String {
+    let mut hash = content_hash(&format!("{salt}:{value}"));
+    hash.truncate(REDACTED_HASH_CHARS);
+    hash
+}
+
+/// 将记录转换为脱敏形式，保留顺序、类型、时间、收藏状态与标签
+pub(crate) fn redact_items(items: &[ClipboardItem], salt: &str) -> Vec<RedactedItem> {
+    items
+        .iter()
+        .map(|item| RedactedItem {
+            id: item.id,
+            content_type: item.content_type.clone(),
+            content_length: item.content.len(),
+            content_hash: salted_hash(salt, &item.content),
+            preview_length: item.preview.len(),
+            preview_hash: salted_hash(salt, &item.preview),
+            is_favorite: item.is_favorite,
+            tags: item.tags.clone(),
+            created_at: item.created_at.clone(),
+            source_format: item.source_format.clone(),
+        })
+        .collect()
+}
+
+/// 按 id 从新到旧分页读取全部记录并逐页脱敏，内存中只保留一页原始内容
+pub(crate) fn redact_database(
+    db: &Database,
+    salt: &str,
+    page_size: i64,
+) -> Result<Vec<RedactedItem>> {
+    let mut redacted = Vec::new();
+    let mut before_id = i64::MAX;
+    loop {
+        let page = db.get_items_before_id(before_id, page_size)?;
+        let Some(last) = page.last() else {
+            break;
+        };
+        before_id = last.id;
+        redacted.extend(redact_items(&page, salt));
+    }
+    Ok(redacted)
+}
+
+/// 将全部记录脱敏后写入 JSON 文件；每次导出使用不同的盐
+pub fn export_redacted(db: &Database, path: &Path) -> Result<RedactedExport> {
+    let now = Utc::now();
+    let salt = format!(
+        "{}-{}",
+        now.timestamp_nanos_opt().unwrap_or_default(),
+        std::process::id()
+    );
+
+    let items = redact_database(db, &salt, REDACT_PAGE_SIZE)?;
+    let export = RedactedExport {
+        exported_at: now.to_rfc3339(),
+        item_count: items.len(),
+        items,
+    };
+    fs::write(path, serde_json::to_string_pretty(&export)?)?;
+    Ok(export)
+}
+
+/// Windows 保留的设备名（不区分大小写，带扩展名也不可用），如 `CON`、`nul.txt`、`COM1`
+fn is_reserved_windows_name(stem: &str) -> bool {
+    let base = stem.split('.').next().unwrap_or_default().trim_end();
+    let upper = base.to_ascii_uppercase();
+    match upper.as_str() {
+        "CON" | "PRN" | "AUX" | "NUL" => true,
+        _ => upper
+            .strip_prefix("COM")
+            .or_else(|| upper.strip_prefix("LPT"))
+            .is_some_and(|digit| matches!(digit.as_bytes(), [b'1'..=b'9'])),
+    }
+}
+
+/// 将预览的首行转换为安全的文件名片段：去掉各平台的非法字符与控制字符，
+/// 合并空白、去掉结尾的点和空格并截断长度；结果为空时返回 "item"，
+/// 与 Windows 保留设备名冲突时加上 `_` 前缀
+pub(crate) fn sanitize_file_stem(preview: &str) -> String {
+    let first_line = preview.lines().next().unwrap_or_default();
+    let cleaned: String = first_line
+        .chars()
+        .filter(|ch| !ch.is_control() && !r#"<>:"/\|?*"#.contains(*ch))
+        .collect();
+
+    let collapsed = cleaned.split_whitespace().collect::<Vec<_>>().join(" ");
+    let truncated: String = collapsed.chars().take(MAX_STEM_CHARS).collect();
+    let stem = truncated.trim_end_matches(['.', ' ']).trim_start();
+
+    if stem.is_empty() {
+        "item".to_string()
+    } else if is_reserved_windows_name(stem) {
+        format!("_{stem}")
+    } else {
+        stem.to_string()
+    }
+}
+
+/// 返回目录中尚未被占用的路径，重名时依次追加 `-2`、`-3`……
+pub(crate) fn unique_path(dir: &Path, stem: &str, extension: &str) -> PathBuf {
+    let candidate = dir.join(format!("{stem}.{extension}"));
+    if !candidate.exists() {
+        return candidate;
+    }
+
+    (2..)
+        .map(|counter| dir.join(format!("{stem}-{counter}.{extension}")))
+        .find(|path| !path.exists())
+        .expect("unbounded counter always yields a free path")
+}
+
+/// 将记录逐条写入目录：文本为 `.txt`，图片为 `.png`，文件记录写成列出路径的 `.txt` 清单。
+/// 文件名形如 `001-<预览>.txt`，返回写入的路径
+pub fn export_items_to_folder(items: &[ClipboardItem], dir: &Path) -> Result<Vec<PathBuf>> {
+    fs::create_dir_all(dir)?;
+
+    let mut written = Vec::with_capacity(items.len());
+    for (index, item) in items.iter().enumerate() {
+        let stem = format!("{:03}-{}", index + 1, sanitize_file_stem(&item.preview));
+        let (extension, bytes) = match item.content_type.as_str() {
+            "image" => ("png", BASE64.decode(&item.content)?),
+            "file" => {
+                let paths: Vec<String> = serde_json::from_str(&item.content)?;
+                ("txt", paths.join("\n").into_bytes())
+            }
+            "text" => ("txt", item.content.clone().into_bytes()),
+            other => return Err(anyhow!("Unsupported content type: {other}")),
+        };
+
+        let path = unique_path(dir, &stem, extension);
+        fs::write(&path, bytes)?;
+        written.push(path);
+    }
+
+    Ok(written)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn item(id: i64, content: &str, tags: &[&str]) -> ClipboardItem {
+        ClipboardItem {
+            id,
+            content_type: "text".into(),
+            content: content.into(),
+            preview: content.into(),
+            is_favorite: false,
+            tags: tags.iter().map(|tag| tag.to_string()).collect(),
+            created_at: "2024-01-01 00:00:00".into(),
+            source_format: Some("CF_UNICODETEXT".into()),
+            thumbnail: None,
+            source_title: Some("Secret Project - Editor".into()),
+        }
+    }
+
+    #[test]
+    fn redacted_items_hide_content_and_titles() {
+        let items = vec![
+            item(1, "hunter2 password", &["client-acme"]),
+            item(2, "hunter2 password", &["client-acme", "work"]),
+        ];
+        let redacted = redact_items(&items, "salt-a");
+        let json = serde_json::to_string(&redacted).unwrap();
+
+        for secret in ["hunter2", "Secret Project"] {
+            assert!(!json.contains(secret), "{secret} leaked into {json}");
+        }
+        assert!(!json.contains("source_title"));
+
+        // 标签按原名保留
+        assert_eq!(redacted[0].tags, vec!["client-acme"]);
+        assert_eq!(redacted[1].tags, vec!["client-acme", "work"]);
+
+        // 同一次导出中相同内容的哈希一致
+        assert_eq!(redacted[0].content_hash, redacted[1].content_hash);
+        assert_eq!(redacted[0].preview_hash, redacted[1].preview_hash);
+        assert_eq!(redacted[0].content_length, "hunter2 password".len());
+
+        // 换盐后哈希不同，无法跨导出关联
+        let other = redact_items(&items, "salt-b");
+        assert_ne!(redacted[0].content_hash, other[0].content_hash);
+    }
+
+    #[test]
+    fn redacted_export_pages_through_database() {
+        let db = Database::new(PathBuf::from(":memory:")).unwrap();
+        let ids: Vec<i64> = (0..5)
+            .map(|i| {
+                let content = format!("secret note {i}");
+                db.add_item("text", &content, &content, None, None, None)
+                    .unwrap()
+            })
+            .collect();
+        db.add_item_tag(ids[0], "client-acme").unwrap();
+
+        // 每页 2 条，需要读取 3 页；结果按 id 从新到旧且没有重复
+        let redacted = redact_database(&db, "salt", 2).unwrap();
+        let exported: Vec<i64> = redacted.iter().map(|item| item.id).collect();
+        let mut expected = ids.clone();
+        expected.reverse();
+        assert_eq!(exported, expected);
+        assert_eq!(redacted[4].tags, vec!["client-acme"]);
+        assert_eq!(redacted[4].content_length, "secret note 0".len());
+
+        let path = std::env::temp_dir().join(format!(
+            "cat-clipboard-redacted-{}.json",
+            std::process::id()
+        ));
+        let export = export_redacted(&db, &path).unwrap();
+        assert_eq!(export.item_count, 5);
+        let written = fs::read_to_string(&path).unwrap();
+        assert!(!written.contains("secret note"));
+        assert!(written.contains("client-acme"));
+
+        let _ = fs::remove_file(path);
+    }
+
+    #[test]
+    fn sanitize_strips_illegal_characters() {
+        assert_eq!(sanitize_file_stem("a<b>:c\"d/e\\f|g?h*i"), "abcdefghi");
+        assert_eq!(
+            sanitize_file_stem("  hello   world  \nsecond line"),
+            "hello world"
+        );
+        assert_eq!(sanitize_file_stem("trailing dots... "), "trailing dots");
+        assert_eq!(sanitize_file_stem("\t\u{7}"), "item");
+        assert_eq!(sanitize_file_stem(""), "item");
+        assert_eq!(
+            sanitize_file_stem(&"长".repeat(100)).chars().count(),
+            MAX_STEM_CHARS
+        );
+    }
+
+    #[test]
+    fn sanitize_prefixes_reserved_windows_names() {
+        assert_eq!(sanitize_file_stem("CON"), "_CON");
+        assert_eq!(sanitize_file_stem("nul"), "_nul");
+        assert_eq!(sanitize_file_stem("com1"), "_com1");
+        assert_eq!(sanitize_file_stem("LPT9.txt"), "_LPT9.txt");
+        assert_eq!(sanitize_file_stem("aux .log"), "_aux .log");
+
+        assert_eq!(sanitize_file_stem("COM0"), "COM0");
+        assert_eq!(sanitize_file_stem("COM10"), "COM10");
+        assert_eq!(sanitize_file_stem("console"), "console");
+        assert_eq!(sanitize_file_stem("LPT"), "LPT");
+        assert_eq!(sanitize_file_stem("a长"), "a长");
+    }
+
+    #[test]
+    fn unique_path_appends_counter() {
+        let dir = std::env::temp_dir().join(format!("cat-clipboard-export-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+
+        let first = unique_path(&dir, "note", "txt");
+        assert_eq!(first, dir.join("note.txt"));
+        fs::write(&first, "1").unwrap();
+
+        let second = unique_path(&dir, "note", "txt");
+        assert_eq!(second, dir.join("note-2.txt"));
+        fs::write(&second, "2").unwrap();
+
+        assert_eq!(unique_path(&dir, "note", "txt"), dir.join("note-3.txt"));
+        assert_eq!(unique_path(&dir, "note", "png"), dir.join("note.png"));
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
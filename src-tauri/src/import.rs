@@ -0,0 +1,610 @@
+use anyhow::{anyhow, Result};
+use chrono::{DateTime, NaiveDateTime, TimeZone, Utc};
+use rusqlite::{params, Connection, OpenFlags, OptionalExtension};
+use serde::Serialize;
+use serde_json::Value;
+use std::path::Path;
+
+use crate::clipboard::{
+    build_file_preview, build_text_preview, build_thumbnail, dib_to_png_base64,
+};
+
+/// 外部剪切板工具导出文件的格式
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExternalFormat {
+    /// 通用 JSON 导出：记录数组，或包含 `items` / `clips` / `entries` 数组的对象
+    Json,
+    /// Ditto 的 SQLite 数据库（`Ditto.db`，或其“备份数据库”生成的副本）
+    Ditto,
+}
+
+impl ExternalFormat {
+    pub fn parse(name: &str) -> Result<Self> {
+        match name.trim().to_ascii_lowercase().as_str() {
+            "json" => Ok(Self::Json),
+            "ditto" | "ditto-db" => Ok(Self::Ditto),
+            other => Err(anyhow!("Unsupported import format: {other}")),
+        }
+    }
+}
+
+/// 从外部导出文件中解析出的一条记录
+#[derive(Debug, Clone)]
+pub struct ImportedEntry {
+    pub content_type: String,
+    pub content: String,
+    pub preview: String,
+    pub created_at: String, // RFC3339
+    pub is_favorite: bool,
+    pub thumbnail: Option<String>,
+}
+
+/// 解析结果：有效记录以及被跳过的格式错误条目数量
+#[derive(Debug, Default)]
+pub struct ParsedExport {
+    pub entries: Vec<ImportedEntry>,
+    pub skipped: usize,
+}
+
+/// 导入结果，返回给前端
+#[derive(Debug, Clone, Serialize)]
+pub struct ImportReport {
+    pub imported: usize,
+    pub skipped: usize,    // 格式错误被跳过的条目
+    pub duplicates: usize, // 时间与内容都已存在而被跳过的条目
+    pub disallowed: usize, // 类型不在记录范围内而被跳过的条目
+}
+
+/// 按格式解析外部导出文件
+pub fn parse_external(format: ExternalFormat, path: &Path) -> Result<ParsedExport> {
+    match format {
+        ExternalFormat::Json => parse_json_export(&std::fs::read_to_string(path)?),
+        ExternalFormat::Ditto => parse_ditto_db(path),
+    }
+}
+
+/// 解析通用 JSON 导出：顶层为数组，或包含 `items` / `clips` / `entries` 数组的对象
+fn parse_json_export(raw: &str) -> Result<ParsedExport> {
+    let root: Value = serde_json::from_str(raw)?;
+    let records = match &root {
+        Value::Array(records) => records,
+        Value::Object(map) => ["items", "clips", "entries"]
+            .iter()
+            .find_map(|key| map.get(*key).and_then(Value::as_array))
+            .ok_or_else(|| anyhow!("No item array found in JSON export"))?,
+        _ => return Err(anyhow!("Unexpected JSON export structure")),
+    };
+
+    let mut parsed = ParsedExport::default();
+    for record in records {
+        match parse_json_record(record) {
+            Some(entry) => parsed.entries.push(entry),
+            None => parsed.skipped += 1,
+        }
+    }
+
+    Ok(parsed)
+}
+
+fn parse_json_record(record: &Value) -> Option<ImportedEntry> {
+    let object = record.as_object()?;
+    let field = |names: &[&str]| names.iter().find_map(|name| object.get(*name));
+
+    let content_type = field(&["type", "content_type", "format"])
+        .and_then(Value::as_str)
+        .map(normalize_content_type)
+        .unwrap_or(Some("text"))?;
+
+    let raw_content = field(&["content", "text", "data", "value"])?;
+    let mut thumbnail = None;
+    let (content, preview) = match content_type {
+        "file" => {
+            let files: Vec<String> = match raw_content {
+                Value::Array(paths) => paths
+                    .iter()
+                    .filter_map(|path| path.as_str().map(str::to_string))
+                    .collect(),
+                Value::String(paths) => paths
+                    .lines()
+                    .map(str::trim)
+                    .filter(|path| !path.is_empty())
+                    .map(str::to_string)
+                    .collect(),
+                _ => return None,
+            };
+            if files.is_empty() {
+                return None;
+            }
+            let preview = build_file_preview(&files, false);
+            (serde_json::to_string(&files).ok()?, preview)
+        }
+        "image" => {
+            let data = raw_content.as_str()?.trim();
+            if data.is_empty() {
+                return None;
+            }
+            thumbnail = build_thumbnail(data);
+            (data.to_string(), "图片".to_string())
+        }
+        _ => {
+            let text = raw_content.as_str()?.replace("\r\n", "\n");
+            if text.trim().is_empty() {
+                return None;
+            }
+            let preview = build_text_preview(&text, false);
+            (text, preview)
+        }
+    };
+
+    let created_at = match field(&["timestamp", "created_at", "date", "time"]) {
+        Some(value) => normalize_timestamp(value)?,
+        None => Utc::now().to_rfc3339(),
+    };
+
+    let is_favorite = field(&["favorite", "is_favorite", "pinned", "starred"])
+        .map(|value| match value {
+            Value::Bool(flag) => *flag,
+            Value::Number(number) => number.as_i64().unwrap_or(0) != 0,
+            Value::String(text) => matches!(text.as_str(), "true" | "1" | "yes"),
+            _ => false,
+        })
+        .unwrap_or(false);
+
+    Some(ImportedEntry {
+        content_type: content_type.to_string(),
+        content,
+        preview,
+        created_at,
+        is_favorite,
+        thumbnail,
+    })
+}
+
+/// 解析 Ditto 数据库：`Main` 表每行一条剪切（`lDate` 为 Unix 秒，
+/// `lDontAutoDelete` 非零表示“永不自动删除”，视为收藏，`bIsGroup` 为分组而非剪切），
+/// `Data` 表按 `lParentID` 保存该剪切的各剪切板格式原始数据
+fn parse_ditto_db(path: &Path) -> Result<ParsedExport> {
+    let conn = Connection::open_with_flags(path, OpenFlags::SQLITE_OPEN_READ_ONLY)?;
+    let mut clips = conn.prepare(
+        "SELECT lID, lDate, mText, lDontAutoDelete FROM Main
+         WHERE COALESCE(bIsGroup, 0) = 0
+         ORDER BY lDate ASC, lID ASC",
+    )?;
+    let mut data = conn.prepare(
+        "SELECT ooData FROM Data WHERE lParentID = ?1 AND strClipBoardFormat = ?2
+         ORDER BY lID LIMIT 1",
+    )?;
+
+    let rows = clips
+        .query_map([], |row| {
+            Ok((
+                row.get::<_, i64>(0)?,
+                row.get::<_, Option<i64>>(1)?,
+                row.get::<_, Option<String>>(2)?,
+                row.get::<_, Option<i64>>(3)?,
+            ))
+        })?
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let mut parsed = ParsedExport::default();
+    for (id, date, text, dont_auto_delete) in rows {
+        let mut format_data = |format: &str| -> Result<Option<Vec<u8>>> {
+            Ok(data
+                .query_row(params![id, format], |row| row.get(0))
+                .optional()?)
+        };
+        let entry = ditto_entry(
+            date,
+            text,
+            dont_auto_delete.unwrap_or(0) != 0,
+            &mut format_data,
+        )?;
+        match entry {
+            Some(entry) => parsed.entries.push(entry),
+            None => parsed.skipped += 1,
+        }
+    }
+
+    Ok(parsed)
+}
+
+/// 将一条 Ditto 剪切转换为导入记录：依次尝试 Unicode 文本、ANSI 文本、文件列表与 DIB 图片，
+/// 都没有时回退到 `mText`；时间缺失或内容为空时返回 None
+fn ditto_entry(
+    date: Option<i64>,
+    text: Option<String>,
+    is_favorite: bool,
+    format_data: &mut dyn FnMut(&str) -> Result<Option<Vec<u8>>>,
+) -> Result<Option<ImportedEntry>> {
+    let Some(created_at) = date.and_then(|secs| Utc.timestamp_opt(secs, 0).single()) else {
+        return Ok(None);
+    };
+    let entry = |content_type: &str, content: String, preview: String, thumbnail| {
+        Some(ImportedEntry {
+            content_type: content_type.to_string(),
+            content,
+            preview,
+            created_at: created_at.to_rfc3339(),
+            is_favorite,
+            thumbnail,
+        })
+    };
+
+    let clip_text = match format_data("CF_UNICODETEXT")? {
+        Some(bytes) => Some(decode_utf16_z(&bytes)),
+        None => format_data("CF_TEXT")?.map(|bytes| decode_ansi_z(&bytes)),
+    };
+    if let Some(clip_text) = clip_text.filter(|text| !text.trim().is_empty()) {
+        let clip_text = clip_text.replace("\r\n", "\n");
+        let preview = build_text_preview(&clip_text, false);
+        return Ok(entry("text", clip_text, preview, None));
+    }
+
+    if let Some(files) = format_data("CF_HDROP")?.and_then(|bytes| parse_dropfiles(&bytes)) {
+        let preview = build_file_preview(&files, false);
+        let Ok(content) = serde_json::to_string(&files) else {
+            return Ok(None);
+        };
+        return Ok(entry("file", content, preview, None));
+    }
+
+    if let Some(dib) = format_data("CF_DIB")? {
+        let Ok((png, _, _)) = dib_to_png_base64(&dib) else {
+            return Ok(None);
+        };
+        let thumbnail = build_thumbnail(&png);
+        return Ok(entry("image", png, "图片".to_string(), thumbnail));
+    }
+
+    // 没有可识别的格式数据时使用 Ditto 保存的文本描述
+    Ok(text
+        .map(|text| text.replace("\r\n", "\n"))
+        .filter(|text| !text.trim().is_empty())
+        .and_then(|text| {
+            let preview = build_text_preview(&text, false);
+            entry("text", text, preview, None)
+        }))
+}
+
+/// 解码以 NUL 结尾的 UTF-16LE 文本（`CF_UNICODETEXT`）
+fn decode_utf16_z(bytes: &[u8]) -> String {
+    let units: Vec<u16> = bytes
+        .chunks_exact(2)
+        .map(|pair| u16::from_le_bytes([pair[0], pair[1]]))
+        .take_while(|unit| *unit != 0)
+        .collect();
+    String::from_utf16_lossy(&units)
+}
+
+/// 解码以 NUL 结尾的 ANSI 文本（`CF_TEXT`）；代码页未知，按 UTF-8 宽松解码
+fn decode_ansi_z(bytes: &[u8]) -> String {
+    let end = bytes
+        .iter()
+        .position(|byte| *byte == 0)
+        .unwrap_or(bytes.len());
+    String::from_utf8_lossy(&bytes[..end]).into_owned()
+}
+
+/// 解析 `CF_HDROP` 的 DROPFILES 结构：20 字节头（`pFiles` 偏移与 `fWide` 标志），
+/// 之后是以 NUL 分隔、双 NUL 结尾的路径列表
+fn parse_dropfiles(bytes: &[u8]) -> Option<Vec<String>> {
+    let read_u32 = |offset: usize| {
+        bytes
+            .get(offset..offset + 4)
+            .map(|raw| u32::from_le_bytes([raw[0], raw[1], raw[2], raw[3]]))
+    };
+    let offset = read_u32(0)? as usize;
+    let wide = read_u32(16)? != 0;
+    let list = bytes.get(offset..)?;
+
+    let joined = if wide {
+        let units: Vec<u16> = list
+            .chunks_exact(2)
+            .map(|pair| u16::from_le_bytes([pair[0], pair[1]]))
+            .collect();
+        String::from_utf16_lossy(&units)
+    } else {
+        String::from_utf8_lossy(list).into_owned()
+    };
+    let files: Vec<String> = joined
+        .split('\0')
+        .take_while(|path| !path.is_empty())
+        .map(str::to_string)
+        .collect();
+    (!files.is_empty()).then_some(files)
+}
+
+/// 将外部工具的类型名映射到本应用的内容类型
+fn normalize_content_type(name: &str) -> Option<&'static str> {
+    match name.trim().to_ascii_lowercase().as_str() {
+        "text" | "cf_text" | "cf_unicodetext" | "unicode" | "html" | "rtf" => Some("text"),
+        "file" | "files" | "cf_hdrop" => Some("file"),
+        "image" | "bitmap" | "png" | "cf_dib" | "cf_bitmap" => Some("image"),
+        _ => None,
+    }
+}
+
+/// 将各种时间戳表示统一为 RFC3339（UTC）
+fn normalize_timestamp(value: &Value) -> Option<String> {
+    let parsed: DateTime<Utc> = match value {
+        Value::Number(number) => {
+            let raw = number.as_i64()?;
+            // 超过 10^11 视为毫秒时间戳
+            if raw.abs() >= 100_000_000_000 {
+                Utc.timestamp_millis_opt(raw).single()?
+            } else {
+                Utc.timestamp_opt(raw, 0).single()?
+            }
+        }
+        Value::String(text) => {
+            let text = text.trim();
+            if let Ok(parsed) = DateTime::parse_from_rfc3339(text) {
+                parsed.with_timezone(&Utc)
+            } else if let Ok(raw) = text.parse::<i64>() {
+                return normalize_timestamp(&Value::from(raw));
+            } else {
+                [
+                    "%Y-%m-%d %H:%M:%S",
+                    "%Y-%m-%dT%H:%M:%S",
+                    "%Y/%m/%d %H:%M:%S",
+                ]
+                .iter()
+                .find_map(|pattern| NaiveDateTime::parse_from_str(text, pattern).ok())
+                .map(|naive| naive.and_utc())?
+            }
+        }
+        _ => return None,
+    };
+
+    Some(parsed.to_rfc3339())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::Database;
+    use serde_json::json;
+    use std::path::PathBuf;
+
+    #[test]
+    fn parses_array_and_object_roots() {
+        let array = parse_json_export(r#"[{"text": "one"}, {"content": "two"}]"#).unwrap();
+        assert_eq!(array.entries.len(), 2);
+        assert_eq!(array.entries[1].content, "two");
+
+        for key in ["items", "clips", "entries"] {
+            let raw = json!({ key: [{ "text": "nested" }] }).to_string();
+            let parsed = parse_json_export(&raw).unwrap();
+            assert_eq!(parsed.entries.len(), 1, "root key {key}");
+            assert_eq!(parsed.entries[0].content, "nested");
+        }
+
+        assert!(parse_json_export(r#"{"other": []}"#).is_err());
+        assert!(parse_json_export(r#""just a string""#).is_err());
+        assert!(parse_json_export("not json").is_err());
+    }
+
+    #[test]
+    fn counts_skipped_records() {
+        let raw = json!([
+            { "text": "kept", "favorite": true },
+            { "text": "   " },
+            { "type": "unknown", "text": "bad type" },
+            { "type": "file", "content": [] },
+            { "text": "bad time", "timestamp": "yesterday" },
+            "not an object",
+            { "type": "files", "content": "C:\\a.txt\r\nC:\\b.txt" },
+        ])
+        .to_string();
+        let parsed = parse_json_export(&raw).unwrap();
+
+        assert_eq!(parsed.skipped, 5);
+        assert_eq!(parsed.entries.len(), 2);
+        assert!(parsed.entries[0].is_favorite);
+        assert_eq!(parsed.entries[1].content_type, "file");
+        assert_eq!(parsed.entries[1].content, r#"["C:\\a.txt","C:\\b.txt"]"#);
+    }
+
+    /// Ditto 建库语句中的 `Main` 与 `Data` 表（与 Ditto 自身的表结构一致）
+    const DITTO_SCHEMA: &str = "
+        CREATE TABLE Main(lID INTEGER PRIMARY KEY AUTOINCREMENT, lDate INTEGER, mText TEXT,
+            lShortCut INTEGER, lDontAutoDelete INTEGER, CRC INTEGER, bIsGroup INTEGER,
+            lParentID INTEGER, QuickPasteText TEXT, clipOrder REAL, clipGroupOrder REAL,
+            globalShortCut INTEGER, lastPasteDate INTEGER, stickyClipOrder REAL,
+            stickyClipGroupOrder REAL, MoveToGroupShortCut INTEGER,
+            GlobalMoveToGroupShortCut INTEGER);
+        CREATE TABLE Data(lID INTEGER PRIMARY KEY AUTOINCREMENT, lParentID INTEGER,
+            strClipBoardFormat TEXT, ooData BLOB);";
+
+    fn utf16_z(text: &str) -> Vec<u8> {
+        text.encode_utf16()
+            .chain([0])
+            .flat_map(u16::to_le_bytes)
+            .collect()
+    }
+
+    /// 宽字符 DROPFILES：20 字节头后接双 NUL 结尾的路径列表
+    fn dropfiles(paths: &[&str]) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        bytes.extend_from_slice(&20u32.to_le_bytes());
+        bytes.extend_from_slice(&[0; 12]);
+        bytes.extend_from_slice(&1u32.to_le_bytes());
+        for path in paths {
+            bytes.extend(utf16_z(path));
+        }
+        bytes.extend_from_slice(&[0, 0]);
+        bytes
+    }
+
+    /// 1x1 的 32 位 DIB（BITMAPINFOHEADER + 一个 BGRA 像素）
+    fn one_pixel_dib() -> Vec<u8> {
+        let mut dib = Vec::new();
+        dib.extend_from_slice(&40u32.to_le_bytes());
+        dib.extend_from_slice(&1i32.to_le_bytes());
+        dib.extend_from_slice(&1i32.to_le_bytes());
+        dib.extend_from_slice(&1u16.to_le_bytes());
+        dib.extend_from_slice(&32u16.to_le_bytes());
+        dib.extend_from_slice(&[0; 24]);
+        dib.extend_from_slice(&[40, 120, 240, 255]);
+        dib
+    }
+
+    /// 按 Ditto 的表结构生成一个示例数据库
+    fn ditto_fixture(path: &Path) {
+        let _ = std::fs::remove_file(path);
+        let conn = Connection::open(path).unwrap();
+        conn.execute_batch(DITTO_SCHEMA).unwrap();
+
+        let clip = |date: Option<i64>, text: &str, keep: i64, group: i64| -> i64 {
+            conn.execute(
+                "INSERT INTO Main (lDate, mText, lDontAutoDelete, bIsGroup) VALUES (?1, ?2, ?3, ?4)",
+                params![date, text, keep, group],
+            )
+            .unwrap();
+            conn.last_insert_rowid()
+        };
+        let unicode = clip(Some(1_700_000_000), "hello ditto", 1, 0);
+        let ansi = clip(Some(1_700_000_100), "ansi", 0, 0);
+        clip(Some(1_700_000_150), "My group", 0, 1);
+        let files = clip(Some(1_700_000_200), "CF_HDROP", 0, 0);
+        let image = clip(Some(1_700_000_300), "CF_DIB", 0, 0);
+        clip(Some(1_700_000_400), "description only", 0, 0);
+        clip(None, "no date", 0, 0);
+        clip(Some(1_700_000_500), "   ", 0, 0);
+
+        let data = [
+            (
+                unicode,
+                "CF_UNICODETEXT",
+                utf16_z("hello ditto\r\nsecond line"),
+            ),
+            (unicode, "HTML Format", b"<b>hello</b>".to_vec()),
+            (ansi, "CF_TEXT", b"ansi text\0".to_vec()),
+            (files, "CF_HDROP", dropfiles(&["C:\\a.txt", "C:\\b.txt"])),
+            (image, "CF_DIB", one_pixel_dib()),
+        ];
+        for (parent, format, blob) in data {
+            conn.execute(
+                "INSERT INTO Data (lParentID, strClipBoardFormat, ooData) VALUES (?1, ?2, ?3)",
+                params![parent, format, blob],
+            )
+            .unwrap();
+        }
+    }
+
+    #[test]
+    fn format_names_parse() {
+        assert_eq!(
+            ExternalFormat::parse(" JSON ").unwrap(),
+            ExternalFormat::Json
+        );
+        assert_eq!(
+            ExternalFormat::parse("Ditto").unwrap(),
+            ExternalFormat::Ditto
+        );
+        assert!(ExternalFormat::parse("ditto-json").is_err());
+        assert!(ExternalFormat::parse("csv").is_err());
+    }
+
+    #[test]
+    fn ditto_database_imports_into_store() {
+        let path =
+            std::env::temp_dir().join(format!("cat-clipboard-ditto-{}.db", std::process::id()));
+        ditto_fixture(&path);
+
+        let parsed = parse_external(ExternalFormat::parse("ditto").unwrap(), &path).unwrap();
+        // 分组行不是剪切，不计入；缺少时间与内容为空白的剪切被跳过
+        assert_eq!(parsed.skipped, 2);
+        let summary: Vec<(&str, &str)> = parsed
+            .entries
+            .iter()
+            .map(|entry| (entry.content_type.as_str(), entry.content.as_str()))
+            .collect();
+        assert_eq!(summary[0], ("text", "hello ditto\nsecond line"));
+        assert_eq!(summary[1], ("text", "ansi text"));
+        assert_eq!(summary[2], ("file", r#"["C:\\a.txt","C:\\b.txt"]"#));
+        assert_eq!(summary[3].0, "image");
+        assert_eq!(summary[4], ("text", "description only"));
+        assert_eq!(parsed.entries[0].created_at, "2023-11-14T22:13:20+00:00");
+        assert!(parsed.entries[0].is_favorite);
+        assert!(!parsed.entries[1].is_favorite);
+        assert!(parsed.entries[3].thumbnail.is_some());
+
+        let db = Database::new(PathBuf::from(":memory:")).unwrap();
+        let counts = db.insert_imported_items(&parsed.entries).unwrap();
+        assert_eq!(counts.inserted, 5);
+        assert_eq!(db.count_items().unwrap(), 5);
+
+        let _ = std::fs::remove_file(path);
+    }
+
+    #[test]
+    fn sample_export_imports_into_store() {
+        let sample = json!({
+            "items": [
+                { "type": "text", "text": "hello", "timestamp": 1_700_000_000, "favorite": true },
+                { "type": "text", "text": "hello", "timestamp": 1_700_000_000 },
+                { "type": "text", "text": "hello", "timestamp": 1_700_000_100 },
+                { "type": "image", "data": "aW1n", "timestamp": 1_700_000_200 },
+                { "type": "files", "content": ["C:\\a.txt"], "timestamp": "2023-11-14 22:20:00" },
+                { "text": "   " },
+                { "type": "unknown", "text": "bad type" },
+            ]
+        })
+        .to_string();
+
+        let db = Database::new(PathBuf::from(":memory:")).unwrap();
+        db.set_capture_allowlist(&["text".to_string(), "file".to_string()]);
+
+        let path =
+            std::env::temp_dir().join(format!("cat-clipboard-import-{}.json", std::process::id()));
+        std::fs::write(&path, sample).unwrap();
+        let parsed = parse_external(ExternalFormat::parse("json").unwrap(), &path).unwrap();
+        assert_eq!(parsed.skipped, 2);
+        let _ = std::fs::remove_file(path);
+
+        let counts = db.insert_imported_items(&parsed.entries).unwrap();
+        assert_eq!(counts.inserted, 3);
+        assert_eq!(counts.duplicates, 1);
+        assert_eq!(counts.disallowed, 1);
+        assert_eq!(db.count_items().unwrap(), 3);
+
+        let items = db.get_items(10, 0).unwrap();
+        assert_eq!(items.iter().filter(|item| item.is_favorite).count(), 1);
+        assert!(items.iter().any(|item| item.content_type == "file"));
+
+        // 再次导入同一文件时全部视为重复
+        let again = db.insert_imported_items(&parsed.entries).unwrap();
+        assert_eq!(again.inserted, 0);
+        assert_eq!(again.duplicates, 4);
+        assert_eq!(again.disallowed, 1);
+    }
+
+    #[test]
+    fn timestamps_accept_seconds_and_milliseconds() {
+        let expected = "2023-11-14T22:13:20+00:00";
+
+        assert_eq!(
+            normalize_timestamp(&json!(1_700_000_000)).as_deref(),
+            Some(expected)
+        );
+        assert_eq!(
+            normalize_timestamp(&json!(1_700_000_000_000i64)).as_deref(),
+            Some(expected)
+        );
+        assert_eq!(
+            normalize_timestamp(&json!("1700000000000")).as_deref(),
+            Some(expected)
+        );
+        assert_eq!(
+            normalize_timestamp(&json!("2023-11-14 22:13:20")).as_deref(),
+            Some(expected)
+        );
+        assert_eq!(
+            normalize_timestamp(&json!("2023-11-15T06:13:20+08:00")).as_deref(),
+            Some(expected)
+        );
+        assert_eq!(normalize_timestamp(&json!("soon")), None);
+        assert_eq!(normalize_timestamp(&json!(true)), None);
+    }
+}
@@ -0,0 +1,101 @@
+use anyhow::Result;
+use serde::Serialize;
+
+use crate::database::Database;
+
+/// 再次启动时唤起已有主窗口需要执行的步骤，按执行顺序排列
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum RestoreStep {
+    /// 重新显示在任务栏中（隐藏到托盘时会被移除）
+    ShowInTaskbar,
+    Unminimize,
+    Show,
+    Focus,
+}
+
+/// 根据主窗口当前状态决定唤起步骤：隐藏到托盘时先恢复任务栏并显示，
+/// 最小化时先还原，最后总是聚焦
+pub(crate) fn restore_steps(visible: bool, minimized: bool) -> Vec<RestoreStep> {
+    let mut steps = Vec::with_capacity(4);
+    if !visible {
+        steps.push(RestoreStep::ShowInTaskbar);
+    }
+    if minimized {
+        steps.push(RestoreStep::Unminimize);
+    }
+    if !visible {
+        steps.push(RestoreStep::Show);
+    }
+    steps.push(RestoreStep::Focus);
+    steps
+}
+
+/// 运行时诊断信息
+#[derive(Debug, Clone, Serialize)]
+pub(crate) struct Diagnostics {
+    /// 当前运行实例的进程 ID；单实例插件保证再次启动的进程会退出，只有它在记录
+    pub pid: u32,
+    pub version: String,
+    /// 指向已不存在的记录或标签的 item_tags 行数
+    pub orphan_associations: i64,
+}
+
+impl Diagnostics {
+    pub(crate) fn collect(db: &Database, version: String) -> Result<Self> {
+        Ok(Self {
+            pid: std::process::id(),
+            version,
+            orphan_associations: db.count_orphan_associations()?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    #[test]
+    fn hidden_window_is_shown_before_focus() {
+        assert_eq!(
+            restore_steps(false, false),
+            vec![
+                RestoreStep::ShowInTaskbar,
+                RestoreStep::Show,
+                RestoreStep::Focus
+            ]
+        );
+    }
+
+    #[test]
+    fn minimized_window_is_restored_before_focus() {
+        assert_eq!(
+            restore_steps(true, true),
+            vec![RestoreStep::Unminimize, RestoreStep::Focus]
+        );
+        assert_eq!(
+            restore_steps(false, true),
+            vec![
+                RestoreStep::ShowInTaskbar,
+                RestoreStep::Unminimize,
+                RestoreStep::Show,
+                RestoreStep::Focus
+            ]
+        );
+    }
+
+    #[test]
+    fn visible_window_is_only_focused() {
+        assert_eq!(restore_steps(true, false), vec![RestoreStep::Focus]);
+    }
+
+    #[test]
+    fn diagnostics_report_current_pid() {
+        let db = Database::new(PathBuf::from(":memory:")).unwrap();
+        let diagnostics = Diagnostics::collect(&db, "1.2.3".to_string()).unwrap();
+
+        assert_eq!(diagnostics.pid, std::process::id());
+        assert_eq!(diagnostics.version, "1.2.3");
+        assert_eq!(diagnostics.orphan_associations, 0);
+    }
+}
@@ -1,57 +1,220 @@
 // Prevents additional console window on Windows in release builds
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
 
+mod auto_tag;
+mod batch;
 mod clipboard;
 mod config;
 mod database;
+mod digest;
+mod export;
+mod import;
+mod instance;
+mod maintenance;
+#[cfg_attr(not(windows), allow(dead_code))]
+mod secrets;
+mod tray;
 
-use clipboard::{ClipboardMonitor, ClipboardSnapshot};
-use config::Config;
-use database::{ClipboardItem, Database};
+use auto_tag::{validate_rules, AutoTagger};
+use batch::EventBatch;
+use chrono::Local;
+use clipboard::{
+    build_thumbnail, text_preview, ClipboardMonitor, ClipboardSnapshot, PRIMARY_SELECTION_FORMAT,
+    PRIMARY_SELECTION_TAG,
+};
+use config::{
+    effective_history_limit, reconcile_autostart, restore_default_config, session_history_limit,
+    AutoTagRule, Config, FilterPreset, WindowAction,
+};
+use database::{
+    AuditEntry, ClipboardItem, Database, HistoryFilter, ImagePruneReport, ItemSummary,
+    ItemWithTagIds, RankingWeights, TagCasePolicy, TrashedItem,
+};
+use digest::DigestFormat;
+use import::{ExternalFormat, ImportReport};
+use instance::{restore_steps, Diagnostics, RestoreStep};
+use maintenance::{Backfill, MaintenanceScheduler};
+use serde::{Deserialize, Serialize};
+use std::io::{BufRead, BufReader};
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::{Arc, Mutex};
-use tauri::{AppHandle, Emitter, Listener, Manager, State, WindowEvent, Wry};
+use std::time::Duration;
+use tauri::image::Image;
 use tauri::menu::{CheckMenuItem, CheckMenuItemBuilder, MenuBuilder, MenuItem, MenuItemBuilder};
 use tauri::tray::{MouseButton, MouseButtonState, TrayIcon, TrayIconBuilder, TrayIconEvent};
+use tauri::{AppHandle, Emitter, Listener, Manager, RunEvent, State, WindowEvent, Wry};
 use tauri_plugin_autostart::ManagerExt;
 
 const TRAY_OPEN_MAIN: &str = "open-main";
 const TRAY_OPEN_SETTINGS: &str = "open-settings";
 const TRAY_TOGGLE_THEME: &str = "toggle-theme";
 const TRAY_TOGGLE_AUTOSTART: &str = "toggle-autostart";
+const TRAY_TOGGLE_PAUSE: &str = "toggle-pause";
 const TRAY_QUIT: &str = "quit";
 
+/// 托盘提示文字的最短刷新间隔，避免频繁写入时反复更新
+const TRAY_TOOLTIP_REFRESH_INTERVAL: Duration = Duration::from_millis(1500);
+
 struct TrayHandles {
-    _icon: TrayIcon<Wry>,
+    icon: TrayIcon<Wry>,
     theme_item: MenuItem<Wry>,
     autostart_item: CheckMenuItem<Wry>,
+    pause_item: CheckMenuItem<Wry>,
 }
 
-fn theme_display_label(theme: &str) -> &'static str {
-    match theme {
-        "light" => "浅色",
-        "dark" => "深色",
-        _ => "自动",
+/// 与记录状态对应的托盘图标：优先使用资源目录中的状态图标，缺失时由默认图标生成
+fn tray_state_icon(app: &AppHandle<Wry>, paused: bool) -> Option<Image<'static>> {
+    let bundled = app
+        .path()
+        .resource_dir()
+        .ok()
+        .and_then(|dir| tray::load_tray_icon(&dir.join(tray::TRAY_ICON_DIR), paused));
+    if let Some((rgba, width, height)) = bundled {
+        return Some(Image::new_owned(rgba, width, height));
+    }
+
+    let base = app.default_window_icon()?;
+    Some(Image::new_owned(
+        tray::tray_icon_rgba(base.rgba(), paused),
+        base.width(),
+        base.height(),
+    ))
+}
+
+/// 暂停或恢复记录，并同步托盘图标、菜单勾选状态与前端
+fn apply_capture_paused(app: &AppHandle<Wry>, state: &AppState, paused: bool) {
+    state.clipboard_monitor.set_paused(paused);
+
+    if let Ok(handles_guard) = state.tray_handles.lock() {
+        if let Some(handles) = handles_guard.as_ref() {
+            let _ = handles.icon.set_icon(tray_state_icon(app, paused));
+            let _ = handles.pause_item.set_checked(paused);
+        }
+    }
+
+    if let Err(err) = app.emit("capture-paused-changed", paused) {
+        eprintln!("Failed to emit capture-paused-changed event: {err:?}");
     }
 }
 
-fn theme_menu_label(theme: &str) -> String {
-    format!("切换主题（当前：{}）", theme_display_label(theme))
+/// 按配置同步托盘菜单的主题文字与开机自启勾选状态
+fn sync_tray_menu(state: &AppState, config: &Config) {
+    let menu = tray::TrayMenuState::from_config(config);
+    if let Ok(handles_guard) = state.tray_handles.lock() {
+        if let Some(handles) = handles_guard.as_ref() {
+            let _ = handles.autostart_item.set_checked(menu.autostart_checked);
+            let _ = handles.theme_item.set_text(menu.theme_label);
+        }
+    }
 }
 
+/// 显示并聚焦主窗口；窗口隐藏到托盘或最小化时先恢复
 fn focus_main_window(app: &AppHandle<Wry>) {
-    if let Some(window) = app.get_webview_window("main") {
-        let _ = window.set_skip_taskbar(false);
-        let _ = window.show();
-        let _ = window.set_focus();
+    let Some(window) = app.get_webview_window("main") else {
+        return;
+    };
+
+    // 状态查询失败时按隐藏且最小化处理，执行全部步骤
+    let visible = window.is_visible().unwrap_or(false);
+    let minimized = window.is_minimized().unwrap_or(true);
+    for step in restore_steps(visible, minimized) {
+        let _ = match step {
+            RestoreStep::ShowInTaskbar => window.set_skip_taskbar(false),
+            RestoreStep::Unminimize => window.unminimize(),
+            RestoreStep::Show => window.show(),
+            RestoreStep::Focus => window.set_focus(),
+        };
     }
 }
 
+/// 标签体系导出文件：标签名以及自动标签规则
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct TagTaxonomy {
+    tags: Vec<String>,
+    #[serde(default)]
+    auto_tag_rules: Vec<AutoTagRule>,
+}
+
+/// 导入标签体系的结果
+#[derive(Debug, Clone, Serialize)]
+struct TagImportReport {
+    tags_added: usize,
+    rules_added: usize,
+}
+
+/// 磁盘上的原始配置与实际生效配置的对照
+#[derive(Debug, Clone, Serialize)]
+struct ConfigComparison {
+    raw: Config,
+    effective: Config,
+    /// 因超出范围或无效而被调整的字段
+    adjusted_fields: Vec<String>,
+}
+
 /// 应用状态
 struct AppState {
     db: Arc<Database>,
     config: Arc<Mutex<Config>>,
-    _clipboard_monitor: Arc<ClipboardMonitor>,
+    clipboard_monitor: Arc<ClipboardMonitor>,
     tray_handles: Arc<Mutex<Option<TrayHandles>>>,
+    tray_tooltip_dirty: Arc<AtomicBool>,
+    /// 仅在本次运行中生效的历史记录上限，不写入配置
+    session_limit: Arc<Mutex<Option<i64>>>,
+    /// 批量操作期间暂停逐条的 `history-updated` 事件
+    event_batch: Arc<EventBatch>,
+    /// 后台补齐派生列，恢复数据库后需要重新启动
+    backfill: Arc<Backfill>,
+}
+
+impl AppState {
+    /// 当前实际生效的历史记录上限
+    fn history_limit(&self) -> i64 {
+        let config_limit = self.config.lock().unwrap().max_history_items;
+        effective_history_limit(config_limit, *self.session_limit.lock().unwrap())
+    }
+
+    /// 标记记录数量已变化，由托盘刷新线程节流更新提示文字
+    fn mark_history_changed(&self) {
+        self.tray_tooltip_dirty.store(true, Ordering::Relaxed);
+    }
+
+    /// 标记记录已变化并通知前端（导入、清空、恢复等批量变化）
+    fn notify_history_changed(&self, app: &AppHandle<Wry>) {
+        self.mark_history_changed();
+        emit_history_updated(app, &self.event_batch, None);
+    }
+
+    /// 按当前上限裁剪历史，有记录被删除时通知前端
+    fn enforce_history_limit(&self, app: &AppHandle<Wry>) -> Result<(), String> {
+        let removed = self
+            .db
+            .maintain_limit(self.history_limit())
+            .map_err(|e| e.to_string())?;
+        emit_history_trimmed(app, &self.event_batch, removed);
+        Ok(())
+    }
+}
+
+/// 发送 `history-updated`，负载为新捕获记录的 id，批量变化时为 null。
+/// 批次进行中时只计数，由 `end_batch` 统一发送一次 `history-refreshed`。
+fn emit_history_updated(app: &AppHandle<Wry>, batch: &EventBatch, item_id: Option<i64>) {
+    if !batch.should_emit() {
+        return;
+    }
+    if let Err(err) = app.emit("history-updated", item_id) {
+        eprintln!("Failed to emit history-updated event: {err:?}");
+    }
+}
+
+/// 因历史上限裁剪了记录时发送 `history-trimmed`，负载为删除数量；批次进行中时同样被抑制
+fn emit_history_trimmed(app: &AppHandle<Wry>, batch: &EventBatch, removed: usize) {
+    if removed == 0 || !batch.should_emit() {
+        return;
+    }
+    if let Err(err) = app.emit("history-trimmed", removed) {
+        eprintln!("Failed to emit history-trimmed event: {err:?}");
+    }
 }
 
 /// 获取历史记录列表
@@ -61,9 +224,99 @@ async fn get_history(
     limit: i64,
     offset: i64,
 ) -> Result<Vec<ClipboardItem>, String> {
+    state.db.get_items(limit, offset).map_err(|e| e.to_string())
+}
+
+/// 获取历史记录列表，标签以 `{id, name}` 形式附带在 `tag_refs` 中
+#[tauri::command]
+async fn get_items_with_tag_ids(
+    state: State<'_, AppState>,
+    limit: i64,
+    offset: i64,
+) -> Result<Vec<ItemWithTagIds>, String> {
     state
         .db
-        .get_items(limit, offset)
+        .get_items_with_tag_ids(limit, offset)
+        .map_err(|e| e.to_string())
+}
+
+/// 重新计算全部记录的搜索文本，返回处理的数量
+#[tauri::command]
+async fn rebuild_search_text(state: State<'_, AppState>) -> Result<usize, String> {
+    state.db.rebuild_search_text().map_err(|e| e.to_string())
+}
+
+/// 按新近程度、使用次数与收藏综合排序的记录
+#[tauri::command]
+async fn get_ranked(state: State<'_, AppState>, limit: i64) -> Result<Vec<ClipboardItem>, String> {
+    let weights = {
+        let config = state.config.lock().unwrap();
+        RankingWeights {
+            recency: config.ranking_recency_weight,
+            usage: config.ranking_usage_weight,
+            favorite: config.ranking_favorite_weight,
+        }
+    };
+
+    state
+        .db
+        .get_ranked(limit, weights)
+        .map_err(|e| e.to_string())
+}
+
+/// 按一天中的小时统计复制次数（使用配置的时区，未配置时使用系统时区）
+#[tauri::command]
+async fn get_hourly_histogram(state: State<'_, AppState>) -> Result<[i64; 24], String> {
+    let offset_minutes = state
+        .config
+        .lock()
+        .unwrap()
+        .timezone_offset_minutes
+        .unwrap_or_else(|| i64::from(Local::now().offset().local_minus_utc() / 60));
+
+    state
+        .db
+        .get_hourly_histogram(offset_minutes)
+        .map_err(|e| e.to_string())
+}
+
+/// 获取尚未打标签的记录
+#[tauri::command]
+async fn get_untagged_items(
+    state: State<'_, AppState>,
+    limit: i64,
+    offset: i64,
+) -> Result<Vec<ClipboardItem>, String> {
+    state
+        .db
+        .get_untagged_items(limit, offset)
+        .map_err(|e| e.to_string())
+}
+
+/// 列出快速筛选预设
+#[tauri::command]
+async fn list_filter_presets(state: State<'_, AppState>) -> Result<Vec<FilterPreset>, String> {
+    let config = state.config.lock().unwrap();
+    Ok(config.filter_presets.clone())
+}
+
+/// 应用按键对应的快速筛选，返回筛选后的记录
+#[tauri::command]
+async fn apply_filter_preset(
+    state: State<'_, AppState>,
+    key: String,
+    limit: i64,
+) -> Result<Vec<ClipboardItem>, String> {
+    let filter = {
+        let config = state.config.lock().unwrap();
+        let preset = config
+            .filter_preset(&key)
+            .ok_or_else(|| format!("No filter preset bound to key {key}"))?;
+        HistoryFilter::parse(&preset.filter).map_err(|e| e.to_string())?
+    };
+    state
+        .db
+        .get_filtered(&filter, limit)
         .map_err(|e| e.to_string())
 }
 
@@ -80,27 +333,149 @@ async fn search_history(
         .map_err(|e| e.to_string())
 }
 
+/// 用正则表达式搜索历史记录
+#[tauri::command]
+async fn search_regex(
+    state: State<'_, AppState>,
+    pattern: String,
+    limit: i64,
+) -> Result<Vec<ClipboardItem>, String> {
+    state
+        .db
+        .search_regex(&pattern, limit)
+        .map_err(|e| e.to_string())
+}
+
+/// 查找引用了指定文件路径的记录
+#[tauri::command]
+async fn find_items_referencing_path(
+    state: State<'_, AppState>,
+    path: String,
+) -> Result<Vec<ClipboardItem>, String> {
+    state
+        .db
+        .find_items_referencing_path(&path)
+        .map_err(|e| e.to_string())
+}
+
+/// 获取单条记录内容的字节数，供列表展示大小而无需传输内容
+#[tauri::command]
+async fn get_item_size(state: State<'_, AppState>, id: i64) -> Result<i64, String> {
+    state.db.get_item_size(id).map_err(|e| e.to_string())
+}
+
+/// 获取内容最大的若干条记录，便于定向清理
+#[tauri::command]
+async fn get_largest_items(
+    state: State<'_, AppState>,
+    limit: i64,
+) -> Result<Vec<ItemSummary>, String> {
+    state.db.get_largest_items(limit).map_err(|e| e.to_string())
+}
+
+/// 获取列表中下一条（更早的）记录，用于键盘导航
+#[tauri::command]
+async fn get_next_item(
+    state: State<'_, AppState>,
+    after_id: i64,
+    wrap: bool,
+) -> Result<Option<ClipboardItem>, String> {
+    state
+        .db
+        .get_next_item(after_id, wrap)
+        .map_err(|e| e.to_string())
+}
+
+/// 获取列表中上一条（更新的）记录，用于键盘导航
+#[tauri::command]
+async fn get_prev_item(
+    state: State<'_, AppState>,
+    before_id: i64,
+    wrap: bool,
+) -> Result<Option<ClipboardItem>, String> {
+    state
+        .db
+        .get_prev_item(before_id, wrap)
+        .map_err(|e| e.to_string())
+}
+
 /// 添加剪切板记录
 #[tauri::command]
 async fn add_clipboard_item(
     state: State<'_, AppState>,
+    app_handle: tauri::AppHandle,
     content_type: String,
     content: String,
     preview: String,
 ) -> Result<i64, String> {
+    let thumbnail = if content_type == "image" {
+        build_thumbnail(&content)
+    } else {
+        None
+    };
     let id = state
         .db
-        .add_item(&content_type, &content, &preview)
+        .add_item(
+            &content_type,
+            &content,
+            &preview,
+            None,
+            None,
+            thumbnail.as_deref(),
+        )
         .map_err(|e| e.to_string())?;
+    state.mark_history_changed();
 
     // 维护历史记录数量上限
-    let config = state.config.lock().unwrap();
-    state
+    state.enforce_history_limit(&app_handle)?;
+
+    Ok(id)
+}
+
+/// 从其他剪切板工具的导出文件迁移历史记录
+#[tauri::command]
+async fn import_external(
+    state: State<'_, AppState>,
+    app_handle: tauri::AppHandle,
+    path: String,
+    format: String,
+) -> Result<ImportReport, String> {
+    let format = ExternalFormat::parse(&format).map_err(|e| e.to_string())?;
+    let parsed = import::parse_external(format, Path::new(&path)).map_err(|e| e.to_string())?;
+
+    let counts = state
         .db
-        .maintain_limit(config.max_history_items)
+        .insert_imported_items(&parsed.entries)
         .map_err(|e| e.to_string())?;
+    state.notify_history_changed(&app_handle);
+    state.enforce_history_limit(&app_handle)?;
 
-    Ok(id)
+    Ok(ImportReport {
+        imported: counts.inserted,
+        skipped: parsed.skipped,
+        duplicates: counts.duplicates,
+        disallowed: counts.disallowed,
+    })
+}
+
+/// 将文本文件的每个非空行导入为独立的文本记录，返回新增数量
+#[tauri::command]
+async fn import_lines(
+    state: State<'_, AppState>,
+    app_handle: tauri::AppHandle,
+    path: String,
+) -> Result<usize, String> {
+    let file = std::fs::File::open(&path).map_err(|e| e.to_string())?;
+    let skip_duplicates = state.config.lock().unwrap().skip_duplicate_imports;
+
+    let imported = state
+        .db
+        .insert_text_lines(BufReader::new(file).lines(), skip_duplicates)
+        .map_err(|e| e.to_string())?;
+    state.notify_history_changed(&app_handle);
+    state.enforce_history_limit(&app_handle)?;
+
+    Ok(imported)
 }
 
 /// 切换收藏状态
@@ -109,34 +484,190 @@ async fn toggle_favorite(state: State<'_, AppState>, id: i64) -> Result<bool, St
     state.db.toggle_favorite(id).map_err(|e| e.to_string())
 }
 
+/// 将记录移到历史顶部（不重新复制）
+#[tauri::command]
+async fn touch_item(
+    state: State<'_, AppState>,
+    app_handle: tauri::AppHandle,
+    id: i64,
+) -> Result<(), String> {
+    state.db.touch_item(id).map_err(|e| e.to_string())?;
+    state.notify_history_changed(&app_handle);
+    Ok(())
+}
+
+/// 原地编辑文本记录的内容，可选择同时移到列表顶部
+#[tauri::command]
+async fn edit_item(
+    state: State<'_, AppState>,
+    app_handle: tauri::AppHandle,
+    id: i64,
+    content: String,
+    bump: Option<bool>,
+) -> Result<(), String> {
+    let preview = text_preview(&content, &state.config.lock().unwrap());
+    state
+        .db
+        .update_content(id, &content, &preview, bump.unwrap_or(false))
+        .map_err(|e| e.to_string())?;
+    state.notify_history_changed(&app_handle);
+    Ok(())
+}
+
 /// 删除记录
 #[tauri::command]
-async fn delete_item(state: State<'_, AppState>, id: i64) -> Result<(), String> {
-    state.db.delete_item(id).map_err(|e| e.to_string())
+async fn delete_item(
+    state: State<'_, AppState>,
+    app_handle: tauri::AppHandle,
+    id: i64,
+) -> Result<(), String> {
+    state.db.delete_item(id).map_err(|e| e.to_string())?;
+    state.notify_history_changed(&app_handle);
+    Ok(())
 }
 
-/// 清空非收藏记录
+/// 清空非收藏记录（启用回收站时可在保留期内恢复）
 #[tauri::command]
-async fn clear_history(state: State<'_, AppState>) -> Result<(), String> {
+async fn clear_history(
+    state: State<'_, AppState>,
+    app_handle: tauri::AppHandle,
+) -> Result<(), String> {
+    let to_trash = state.config.lock().unwrap().trash_enabled;
     state
         .db
-        .clear_non_favorites()
-        .map_err(|e| e.to_string())
+        .clear_non_favorites(to_trash)
+        .map_err(|e| e.to_string())?;
+    state.notify_history_changed(&app_handle);
+    Ok(())
+}
+
+/// 删除早于指定天数的非收藏图片记录，返回删除数量与回收的字节数
+#[tauri::command]
+async fn prune_images_older_than(
+    state: State<'_, AppState>,
+    app_handle: tauri::AppHandle,
+    days: i64,
+) -> Result<ImagePruneReport, String> {
+    let report = state
+        .db
+        .prune_images_older_than(days)
+        .map_err(|e| e.to_string())?;
+    if report.removed > 0 {
+        state.notify_history_changed(&app_handle);
+    }
+    Ok(report)
 }
 
-/// 复制到剪切板
+/// 获取回收站中的记录
 #[tauri::command]
-async fn copy_to_clipboard(content: String) -> Result<(), String> {
-    ClipboardMonitor::set_clipboard_text(&content).map_err(|e| e.to_string())
+async fn get_trash(state: State<'_, AppState>) -> Result<Vec<TrashedItem>, String> {
+    let retention = state.config.lock().unwrap().trash_retention_minutes;
+    state.db.get_trash(retention).map_err(|e| e.to_string())
 }
 
-/// 添加标签
+/// 从回收站恢复记录，`ids` 为空时恢复全部；恢复后按历史上限裁剪
 #[tauri::command]
-async fn add_tag(
+async fn restore_from_trash(
     state: State<'_, AppState>,
-    item_id: i64,
-    tag_name: String,
+    app_handle: tauri::AppHandle,
+    ids: Option<Vec<i64>>,
+) -> Result<usize, String> {
+    let retention = state.config.lock().unwrap().trash_retention_minutes;
+    let restored = state
+        .db
+        .restore_from_trash(&ids.unwrap_or_default(), retention)
+        .map_err(|e| e.to_string())?;
+    state.enforce_history_limit(&app_handle)?;
+    state.notify_history_changed(&app_handle);
+    Ok(restored)
+}
+
+/// 复制到剪切板；`skip_history` 为 true 时这次写入不会被重新记录。
+/// 传入 `item_id` 时累计该记录的使用次数，供综合排序使用
+#[tauri::command]
+async fn copy_to_clipboard(
+    state: State<'_, AppState>,
+    content: String,
+    skip_history: Option<bool>,
+    item_id: Option<i64>,
 ) -> Result<(), String> {
+    if skip_history.unwrap_or(false) {
+        state
+            .clipboard_monitor
+            .set_clipboard_text_without_history(&content)
+            .map_err(|e| e.to_string())?;
+    } else {
+        ClipboardMonitor::set_clipboard_text(&content).map_err(|e| e.to_string())?;
+    }
+
+    if let Some(id) = item_id {
+        state.db.record_copy(id).map_err(|e| e.to_string())?;
+    }
+    Ok(())
+}
+
+/// 返回与当前剪切板内容相同的历史记录 id（不存在时为空）
+#[tauri::command]
+async fn current_clipboard_item(state: State<'_, AppState>) -> Result<Option<i64>, String> {
+    let Some(snapshot) = state
+        .clipboard_monitor
+        .peek_snapshot()
+        .map_err(|e| e.to_string())?
+    else {
+        return Ok(None);
+    };
+
+    state
+        .db
+        .find_by_content(&snapshot.content_type, &snapshot.content)
+        .map_err(|e| e.to_string())
+}
+
+/// 将选中的记录生成可分享的纯文本摘要（不写入历史）
+#[tauri::command]
+async fn make_digest(
+    state: State<'_, AppState>,
+    ids: Vec<i64>,
+    format: String,
+    include_meta: Option<bool>,
+) -> Result<String, String> {
+    let format = DigestFormat::parse(&format).map_err(|e| e.to_string())?;
+    let items = state.db.get_items_by_ids(&ids).map_err(|e| e.to_string())?;
+
+    Ok(digest::build_digest(
+        &items,
+        format,
+        include_meta.unwrap_or(false),
+    ))
+}
+
+/// 将选中的记录逐条导出为目录中的独立文件，返回写入的路径
+#[tauri::command]
+async fn export_items_to_folder(
+    state: State<'_, AppState>,
+    ids: Vec<i64>,
+    dir: String,
+) -> Result<Vec<String>, String> {
+    let items = state.db.get_items_by_ids(&ids).map_err(|e| e.to_string())?;
+    let written =
+        export::export_items_to_folder(&items, Path::new(&dir)).map_err(|e| e.to_string())?;
+
+    Ok(written
+        .iter()
+        .map(|path| path.to_string_lossy().into_owned())
+        .collect())
+}
+
+/// 导出脱敏后的全部历史（内容只保留长度与哈希），便于附在问题报告中，返回导出的记录数
+#[tauri::command]
+async fn export_redacted(state: State<'_, AppState>, path: String) -> Result<usize, String> {
+    let export = export::export_redacted(&state.db, Path::new(&path)).map_err(|e| e.to_string())?;
+    Ok(export.item_count)
+}
+
+/// 添加标签
+#[tauri::command]
+async fn add_tag(state: State<'_, AppState>, item_id: i64, tag_name: String) -> Result<(), String> {
     state
         .db
         .add_item_tag(item_id, &tag_name)
@@ -156,6 +687,75 @@ async fn remove_tag(
         .map_err(|e| e.to_string())
 }
 
+/// 删除标签，可选择把其下的记录改挂到另一个标签
+#[tauri::command]
+async fn delete_tag(
+    state: State<'_, AppState>,
+    tag_name: String,
+    reassign_to: Option<String>,
+) -> Result<(), String> {
+    state
+        .db
+        .delete_tag(&tag_name, reassign_to.as_deref())
+        .map_err(|e| e.to_string())
+}
+
+/// 合并仅大小写不同的重复标签，`policy` 为 "lower" 或 "preserve-first"，返回合并掉的标签数量
+#[tauri::command]
+async fn normalize_tag_case(state: State<'_, AppState>, policy: String) -> Result<usize, String> {
+    let policy = TagCasePolicy::parse(&policy).map_err(|e| e.to_string())?;
+    state
+        .db
+        .normalize_tag_case(policy)
+        .map_err(|e| e.to_string())
+}
+
+/// 将全部标签与自动标签规则导出为 JSON
+#[tauri::command]
+async fn export_tags(state: State<'_, AppState>, path: String) -> Result<(), String> {
+    let taxonomy = TagTaxonomy {
+        tags: state.db.get_all_tags().map_err(|e| e.to_string())?,
+        auto_tag_rules: state.config.lock().unwrap().auto_tag_rules.clone(),
+    };
+    let content = serde_json::to_string_pretty(&taxonomy).map_err(|e| e.to_string())?;
+    std::fs::write(&path, content).map_err(|e| e.to_string())
+}
+
+/// 导入标签体系：只创建缺失的标签并追加缺失的自动标签规则，重复导入不会产生新内容
+#[tauri::command]
+async fn import_tags(
+    state: State<'_, AppState>,
+    app_handle: tauri::AppHandle,
+    path: String,
+) -> Result<TagImportReport, String> {
+    let raw = std::fs::read_to_string(&path).map_err(|e| e.to_string())?;
+    let taxonomy: TagTaxonomy = serde_json::from_str(&raw).map_err(|e| e.to_string())?;
+    validate_rules(&taxonomy.auto_tag_rules).map_err(|e| e.to_string())?;
+
+    let tags_added = state
+        .db
+        .ensure_tags(&taxonomy.tags)
+        .map_err(|e| e.to_string())?;
+
+    let mut updated = state.config.lock().unwrap().clone();
+    let rules_added = updated.merge_auto_tag_rules(&taxonomy.auto_tag_rules);
+    if rules_added > 0 {
+        let updated = updated.sanitized();
+        let config_path = app_handle
+            .path()
+            .app_config_dir()
+            .map_err(|e| e.to_string())?
+            .join("config.json");
+        updated.save(config_path).map_err(|e| e.to_string())?;
+        *state.config.lock().unwrap() = updated;
+    }
+
+    Ok(TagImportReport {
+        tags_added,
+        rules_added,
+    })
+}
+
 /// 获取所有标签
 #[tauri::command]
 async fn get_all_tags(state: State<'_, AppState>) -> Result<Vec<String>, String> {
@@ -175,6 +775,72 @@ async fn get_items_by_tag(
         .map_err(|e| e.to_string())
 }
 
+/// 按标签获取已收藏的项目
+#[tauri::command]
+async fn get_favorites_by_tag(
+    state: State<'_, AppState>,
+    tag_name: String,
+    limit: i64,
+) -> Result<Vec<ClipboardItem>, String> {
+    state
+        .db
+        .get_favorites_by_tag(&tag_name, limit)
+        .map_err(|e| e.to_string())
+}
+
+/// 分页获取操作审计日志
+#[tauri::command]
+async fn get_audit_log(
+    state: State<'_, AppState>,
+    limit: i64,
+    offset: i64,
+) -> Result<Vec<AuditEntry>, String> {
+    state
+        .db
+        .get_audit_log(limit, offset)
+        .map_err(|e| e.to_string())
+}
+
+/// 开始批量操作：在对应的 `end_batch` 之前不再逐条发送 `history-updated` 与 `history-trimmed`，可嵌套
+#[tauri::command]
+async fn begin_batch(state: State<'_, AppState>) -> Result<(), String> {
+    state.event_batch.begin();
+    Ok(())
+}
+
+/// 结束批量操作；最外层批次结束时发送一次 `history-refreshed`（负载为被抑制的事件数量），
+/// 并返回该数量
+#[tauri::command]
+async fn end_batch(state: State<'_, AppState>) -> Result<Option<usize>, String> {
+    Ok(state.event_batch.end())
+}
+
+/// 设置仅在本次运行中生效的历史记录上限并立即裁剪，返回实际采用的值
+#[tauri::command]
+async fn set_session_limit(
+    state: State<'_, AppState>,
+    app_handle: tauri::AppHandle,
+    max: i64,
+) -> Result<i64, String> {
+    let limit = session_history_limit(max).map_err(|e| e.to_string())?;
+    *state.session_limit.lock().unwrap() = Some(limit);
+    state.enforce_history_limit(&app_handle)?;
+    state.mark_history_changed();
+    Ok(limit)
+}
+
+/// 取消会话上限，恢复使用配置中的上限并立即裁剪
+#[tauri::command]
+async fn clear_session_limit(
+    state: State<'_, AppState>,
+    app_handle: tauri::AppHandle,
+) -> Result<(), String> {
+    *state.session_limit.lock().unwrap() = None;
+    state.enforce_history_limit(&app_handle)?;
+    state.mark_history_changed();
+    Ok(())
+}
+
 /// 获取配置
 #[tauri::command]
 async fn get_config(state: State<'_, AppState>) -> Result<Config, String> {
@@ -182,6 +848,29 @@ async fn get_config(state: State<'_, AppState>) -> Result<Config, String> {
     Ok(config.clone())
 }
 
+/// 获取磁盘上未经 sanitize 的配置，并标出实际生效时被调整的字段
+#[tauri::command]
+async fn get_config_raw(
+    state: State<'_, AppState>,
+    app_handle: tauri::AppHandle,
+) -> Result<ConfigComparison, String> {
+    let config_path = app_handle
+        .path()
+        .app_config_dir()
+        .map_err(|e| e.to_string())?
+        .join("config.json");
+
+    let raw = Config::load_raw(&config_path).map_err(|e| e.to_string())?;
+    let adjusted_fields = raw.adjusted_fields().map_err(|e| e.to_string())?;
+    let effective = state.config.lock().unwrap().clone();
+
+    Ok(ConfigComparison {
+        raw,
+        effective,
+        adjusted_fields,
+    })
+}
+
 /// 更新配置
 #[tauri::command]
 async fn update_config(
@@ -189,6 +878,7 @@ async fn update_config(
     new_config: Config,
     app_handle: tauri::AppHandle,
 ) -> Result<(), String> {
+    validate_rules(&new_config.auto_tag_rules).map_err(|e| e.to_string())?;
     let sanitized = new_config.clone().sanitized();
     let config_path = app_handle
         .path()
@@ -196,25 +886,16 @@ async fn update_config(
         .map_err(|e| e.to_string())?
         .join("config.json");
 
-    sanitized
-        .save(config_path)
-        .map_err(|e| e.to_string())?;
+    sanitized.save(config_path).map_err(|e| e.to_string())?;
 
     {
         let mut config = state.config.lock().unwrap();
         *config = sanitized.clone();
     }
+    state.db.set_audit_enabled(sanitized.enable_audit_log);
+    state.db.set_capture_allowlist(&sanitized.capture_allowlist);
 
-    if let Ok(handles_guard) = state.tray_handles.lock() {
-        if let Some(handles) = handles_guard.as_ref() {
-            let _ = handles
-                .autostart_item
-                .set_checked(sanitized.auto_start);
-            let _ = handles
-                .theme_item
-                .set_text(theme_menu_label(&sanitized.theme));
-        }
-    }
+    sync_tray_menu(&state, &sanitized);
 
     Ok(())
 }
@@ -247,9 +928,7 @@ async fn set_autostart(
     let updated = {
         let mut config = state.config.lock().unwrap();
         config.auto_start = enabled;
-        config
-            .save(config_path)
-            .map_err(|e| e.to_string())?;
+        config.save(config_path).map_err(|e| e.to_string())?;
         config.clone()
     };
 
@@ -262,55 +941,174 @@ async fn set_autostart(
     Ok(updated)
 }
 
-/// 重置应用数据
+/// 查询系统层面实际的开机自启状态，并在与配置不一致时同步配置
 #[tauri::command]
-async fn reset_application(
+async fn get_actual_autostart(
     state: State<'_, AppState>,
     app_handle: tauri::AppHandle,
-) -> Result<Config, String> {
+) -> Result<bool, String> {
+    let actual = app_handle
+        .autolaunch()
+        .is_enabled()
+        .map_err(|e| e.to_string())?;
+
+    let configured = state.config.lock().unwrap().auto_start;
+    if let Some(reconciled) = reconcile_autostart(configured, actual) {
+        let config_path = app_handle
+            .path()
+            .app_config_dir()
+            .map_err(|e| e.to_string())?
+            .join("config.json");
+
+        {
+            let mut config = state.config.lock().unwrap();
+            config.auto_start = reconciled;
+            config.save(config_path).map_err(|e| e.to_string())?;
+        }
+
+        if let Ok(handles_guard) = state.tray_handles.lock() {
+            if let Some(handles) = handles_guard.as_ref() {
+                let _ = handles.autostart_item.set_checked(reconciled);
+            }
+        }
+    }
+
+    Ok(actual)
+}
+
+/// 暂停或恢复记录剪切板
+#[tauri::command]
+async fn set_capture_paused(
+    state: State<'_, AppState>,
+    app_handle: tauri::AppHandle,
+    paused: bool,
+) -> Result<(), String> {
+    apply_capture_paused(&app_handle, &state, paused);
+    Ok(())
+}
+
+/// 当前是否已暂停记录
+#[tauri::command]
+async fn get_capture_paused(state: State<'_, AppState>) -> Result<bool, String> {
+    Ok(state.clipboard_monitor.is_paused())
+}
+
+/// 获取运行时诊断信息
+#[tauri::command]
+async fn get_diagnostics(
+    state: State<'_, AppState>,
+    app_handle: tauri::AppHandle,
+) -> Result<Diagnostics, String> {
+    Diagnostics::collect(&state.db, app_handle.package_info().version.to_string())
+        .map_err(|e| e.to_string())
+}
+
+/// 删除悬空的记录-标签关联，返回删除数量
+#[tauri::command]
+async fn repair_orphan_associations(state: State<'_, AppState>) -> Result<usize, String> {
     state
         .db
-        .reset_all()
-        .map_err(|e| e.to_string())?;
+        .repair_orphan_associations()
+        .map_err(|e| e.to_string())
+}
 
-    if let Err(err) = app_handle.autolaunch().disable() {
-        eprintln!("Failed to disable autostart during reset: {err:?}");
+/// 将数据库完整备份到指定文件
+#[tauri::command]
+async fn backup_database(state: State<'_, AppState>, dest_path: String) -> Result<(), String> {
+    state
+        .db
+        .backup_to(Path::new(&dest_path))
+        .map_err(|e| e.to_string())
+}
+
+/// 用备份文件替换当前数据库，需要前端二次确认后传入 `confirm = true`
+#[tauri::command]
+async fn restore_database(
+    state: State<'_, AppState>,
+    app_handle: tauri::AppHandle,
+    src_path: String,
+    confirm: bool,
+) -> Result<(), String> {
+    if !confirm {
+        return Err("Restore must be confirmed: it replaces all current history".to_string());
     }
 
-    let default_config = Config::default().sanitized();
+    state
+        .db
+        .restore_from(Path::new(&src_path))
+        .map_err(|e| e.to_string())?;
+    state
+        .backfill
+        .start(app_handle.clone(), Arc::clone(&state.db));
+    state.notify_history_changed(&app_handle);
+    Ok(())
+}
+
+/// 将配置恢复为默认值，并同步开机自启与托盘菜单。
+/// 快捷键由前端注册，因此发送 `config-reset`（负载为默认配置），由前端据此重新注册。
+fn reset_config_to_defaults(
+    state: &AppState,
+    app_handle: &tauri::AppHandle,
+) -> Result<Config, String> {
     let config_path = app_handle
         .path()
         .app_config_dir()
         .map_err(|e| e.to_string())?
         .join("config.json");
 
-    default_config
-        .save(config_path)
-        .map_err(|e| e.to_string())?;
+    let default_config =
+        restore_default_config(&state.db, &state.config, config_path).map_err(|e| e.to_string())?;
 
-    {
-        let mut config_guard = state.config.lock().unwrap();
-        *config_guard = default_config.clone();
+    let autolaunch = app_handle.autolaunch();
+    let result = if default_config.auto_start {
+        autolaunch.enable()
+    } else {
+        autolaunch.disable()
+    };
+    if let Err(err) = result {
+        eprintln!("Failed to reconcile autostart with default config: {err:?}");
     }
 
-    if let Ok(handles_guard) = state.tray_handles.lock() {
-        if let Some(handles) = handles_guard.as_ref() {
-            let _ = handles.autostart_item.set_checked(default_config.auto_start);
-            let _ = handles
-                .theme_item
-                .set_text(theme_menu_label(&default_config.theme));
-        }
+    sync_tray_menu(state, &default_config);
+
+    if let Err(err) = app_handle.emit("config-reset", &default_config) {
+        eprintln!("Failed to emit config-reset event: {err:?}");
     }
 
     Ok(default_config)
 }
 
+/// 仅恢复默认配置，保留历史记录与标签
+#[tauri::command]
+async fn reset_config(
+    state: State<'_, AppState>,
+    app_handle: tauri::AppHandle,
+) -> Result<Config, String> {
+    reset_config_to_defaults(&state, &app_handle)
+}
+
+/// 重置应用数据
+#[tauri::command]
+async fn reset_application(
+    state: State<'_, AppState>,
+    app_handle: tauri::AppHandle,
+) -> Result<Config, String> {
+    state.db.reset_all().map_err(|e| e.to_string())?;
+    state.notify_history_changed(&app_handle);
+
+    reset_config_to_defaults(&state, &app_handle)
+}
+
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
     tauri::Builder::default()
+        // 单实例插件需最先注册：再次启动时聚焦已有窗口并退出，避免两个监听器重复写入
+        .plugin(tauri_plugin_single_instance::init(|app, _args, _cwd| {
+            focus_main_window(app);
+        }))
         .plugin(tauri_plugin_shell::init())
-    .plugin(tauri_plugin_clipboard_manager::init())
-    .plugin(tauri_plugin_global_shortcut::Builder::new().build())
+        .plugin(tauri_plugin_clipboard_manager::init())
+        .plugin(tauri_plugin_global_shortcut::Builder::new().build())
         .plugin(tauri_plugin_autostart::init(
             tauri_plugin_autostart::MacosLauncher::LaunchAgent,
             None,
@@ -326,38 +1124,104 @@ pub fn run() {
             // 初始化数据库与配置
             let db = Arc::new(Database::new(db_path)?);
             let config = Arc::new(Mutex::new(Config::load(config_path)?));
+            {
+                let guard = config.lock().unwrap();
+                db.set_audit_enabled(guard.enable_audit_log);
+                db.set_capture_allowlist(&guard.capture_allowlist);
+            }
 
             // 初始化剪切板监听器
-            let clipboard_monitor = Arc::new(ClipboardMonitor::new());
+            let clipboard_monitor = Arc::new(ClipboardMonitor::new(Arc::clone(&config)));
             let tray_handles: Arc<Mutex<Option<TrayHandles>>> = Arc::new(Mutex::new(None));
+            let tray_tooltip_dirty = Arc::new(AtomicBool::new(true));
+            let session_limit: Arc<Mutex<Option<i64>>> = Arc::new(Mutex::new(None));
+            // 批次结束或超时作废时发送一次 `history-refreshed`，由前端整体重新加载
+            let event_batch = Arc::new(EventBatch::new({
+                let refresh_handle = app.handle().clone();
+                let tooltip_dirty = Arc::clone(&tray_tooltip_dirty);
+                move |suppressed| {
+                    tooltip_dirty.store(true, Ordering::Relaxed);
+                    if let Err(err) = refresh_handle.emit("history-refreshed", suppressed) {
+                        eprintln!("Failed to emit history-refreshed event: {err:?}");
+                    }
+                }
+            }));
 
             // 启动剪切板监听
             let app_handle = app.handle().clone();
             clipboard_monitor.start(app_handle.clone());
 
+            // 启动后台维护调度
+            let maintenance = Arc::new(MaintenanceScheduler::new());
+            maintenance.start(app_handle.clone(), Arc::clone(&db), Arc::clone(&config));
+
+            // 分批为旧记录补齐派生列，避免大数据库拖慢启动
+            let backfill = Arc::new(Backfill::new());
+            backfill.start(app_handle.clone(), Arc::clone(&db));
+
             // 注册剪切板变化事件处理器
             let db_for_event = Arc::clone(&db);
             let config_for_event = Arc::clone(&config);
+            let maintenance_for_event = Arc::clone(&maintenance);
+            let tooltip_dirty_for_event = Arc::clone(&tray_tooltip_dirty);
+            let session_limit_for_event = Arc::clone(&session_limit);
+            let batch_for_event = Arc::clone(&event_batch);
+            let auto_tagger = AutoTagger::new();
             let notify_handle = app_handle.clone();
 
             app.listen("clipboard-changed", move |event| {
                 let payload = event.payload();
                 match serde_json::from_str::<ClipboardSnapshot>(payload) {
                     Ok(snapshot) => {
-                        if let Ok(id) = db_for_event
-                            .add_item(&snapshot.content_type, &snapshot.content, &snapshot.preview)
-                        {
-                            if let Ok(cfg) = config_for_event.lock() {
-                                if let Err(err) = db_for_event.maintain_limit(cfg.max_history_items) {
-                                    eprintln!("Failed to enforce history limit: {err:?}");
+                        let id = match db_for_event.add_item(
+                            &snapshot.content_type,
+                            &snapshot.content,
+                            &snapshot.preview,
+                            snapshot.source_format.as_deref(),
+                            snapshot.source_title.as_deref(),
+                            snapshot.thumbnail.as_deref(),
+                        ) {
+                            Ok(id) => id,
+                            Err(err) => {
+                                eprintln!("Dropped clipboard item: {err}");
+                                return;
+                            }
+                        };
+
+                        maintenance_for_event.record_activity();
+                        tooltip_dirty_for_event.store(true, Ordering::Relaxed);
+
+                        if snapshot.content_type != "image" {
+                            let rules = config_for_event
+                                .lock()
+                                .map(|cfg| cfg.auto_tag_rules.clone())
+                                .unwrap_or_default();
+                            for tag in auto_tagger.tags_for(&rules, &snapshot.content) {
+                                if let Err(err) = db_for_event.add_item_tag(id, &tag) {
+                                    eprintln!("Failed to apply auto tag {tag}: {err:?}");
                                 }
                             }
+                        }
+
+                        if snapshot.source_format.as_deref() == Some(PRIMARY_SELECTION_FORMAT) {
+                            if let Err(err) = db_for_event.add_item_tag(id, PRIMARY_SELECTION_TAG) {
+                                eprintln!("Failed to tag PRIMARY selection item: {err:?}");
+                            }
+                        }
 
-                            if let Err(err) = notify_handle.emit("history-updated", id) {
-                                eprintln!("Failed to emit history-updated event: {err:?}");
+                        if let Ok(cfg) = config_for_event.lock() {
+                            let session = *session_limit_for_event.lock().unwrap();
+                            let limit = effective_history_limit(cfg.max_history_items, session);
+                            match db_for_event.maintain_limit(limit) {
+                                Ok(removed) => {
+                                    emit_history_trimmed(&notify_handle, &batch_for_event, removed)
+                                }
+                                Err(err) => eprintln!("Failed to enforce history limit: {err:?}"),
                             }
-                            println!("Captured clipboard item #{id} ({})", snapshot.content_type);
                         }
+
+                        emit_history_updated(&notify_handle, &batch_for_event, Some(id));
+                        println!("Captured clipboard item #{id} ({})", snapshot.content_type);
                     }
                     Err(err) => {
                         eprintln!("Failed to parse clipboard payload: {err:?} -> {payload}");
@@ -378,15 +1242,16 @@ pub fn run() {
                     MenuItemBuilder::with_id(TRAY_OPEN_SETTINGS, "打开设置").build(&app_handle)?;
                 let theme_item = MenuItemBuilder::with_id(
                     TRAY_TOGGLE_THEME,
-                    theme_menu_label(&initial_config.theme),
+                    tray::theme_menu_label(&initial_config.theme),
                 )
                 .build(&app_handle)?;
-                let autostart_item = CheckMenuItemBuilder::with_id(
-                    TRAY_TOGGLE_AUTOSTART,
-                    "开机自启",
-                )
-                .checked(initial_config.auto_start)
-                .build(&app_handle)?;
+                let autostart_item =
+                    CheckMenuItemBuilder::with_id(TRAY_TOGGLE_AUTOSTART, "开机自启")
+                        .checked(initial_config.auto_start)
+                        .build(&app_handle)?;
+                let pause_item = CheckMenuItemBuilder::with_id(TRAY_TOGGLE_PAUSE, "暂停记录")
+                    .checked(false)
+                    .build(&app_handle)?;
                 let quit_item = MenuItemBuilder::with_id(TRAY_QUIT, "退出").build(&app_handle)?;
 
                 let tray_menu = MenuBuilder::new(&app_handle)
@@ -394,6 +1259,7 @@ pub fn run() {
                     .item(&open_settings_item)
                     .item(&theme_item)
                     .item(&autostart_item)
+                    .item(&pause_item)
                     .separator()
                     .item(&quit_item)
                     .build()?;
@@ -418,28 +1284,32 @@ pub fn run() {
                         TRAY_TOGGLE_AUTOSTART => {
                             let _ = app.emit("tray-toggle-autostart", ());
                         }
+                        TRAY_TOGGLE_PAUSE => {
+                            if let Some(state) = app.try_state::<AppState>() {
+                                let paused = !state.clipboard_monitor.is_paused();
+                                apply_capture_paused(app, &state, paused);
+                            }
+                        }
                         TRAY_QUIT => app.exit(0),
                         _ => {}
                     })
-                    .on_tray_icon_event(|icon, event| {
-                        match event {
-                            TrayIconEvent::DoubleClick { .. } => {
-                                focus_main_window(icon.app_handle());
-                                let _ = icon.app_handle().emit("tray-open-main", ());
-                            }
-                            TrayIconEvent::Click {
-                                button: MouseButton::Left,
-                                button_state: MouseButtonState::Up,
-                                ..
-                            } => {
-                                focus_main_window(icon.app_handle());
-                                let _ = icon.app_handle().emit("tray-open-main", ());
-                            }
-                            _ => {}
+                    .on_tray_icon_event(|icon, event| match event {
+                        TrayIconEvent::DoubleClick { .. } => {
+                            focus_main_window(icon.app_handle());
+                            let _ = icon.app_handle().emit("tray-open-main", ());
                         }
+                        TrayIconEvent::Click {
+                            button: MouseButton::Left,
+                            button_state: MouseButtonState::Up,
+                            ..
+                        } => {
+                            focus_main_window(icon.app_handle());
+                            let _ = icon.app_handle().emit("tray-open-main", ());
+                        }
+                        _ => {}
                     });
 
-                if let Some(icon_image) = app.default_window_icon().cloned() {
+                if let Some(icon_image) = tray_state_icon(&app_handle, false) {
                     tray_builder = tray_builder.icon(icon_image);
                 }
 
@@ -449,28 +1319,85 @@ pub fn run() {
                     .lock()
                     .expect("tray handles mutex poisoned");
                 *guard = Some(TrayHandles {
-                    _icon: tray_icon,
+                    icon: tray_icon,
                     theme_item,
                     autostart_item,
+                    pause_item,
                 });
             }
 
+            // 节流刷新托盘提示中的记录数量
+            {
+                let db_for_tooltip = Arc::clone(&db);
+                let tray_for_tooltip = Arc::clone(&tray_handles);
+                let dirty_for_tooltip = Arc::clone(&tray_tooltip_dirty);
+                std::thread::spawn(move || loop {
+                    std::thread::sleep(TRAY_TOOLTIP_REFRESH_INTERVAL);
+                    if !dirty_for_tooltip.swap(false, Ordering::Relaxed) {
+                        continue;
+                    }
+
+                    let count = match db_for_tooltip.count_items() {
+                        Ok(count) => count,
+                        Err(err) => {
+                            eprintln!("Failed to count history items: {err:?}");
+                            continue;
+                        }
+                    };
+
+                    if let Ok(handles_guard) = tray_for_tooltip.lock() {
+                        if let Some(handles) = handles_guard.as_ref() {
+                            let _ = handles
+                                .icon
+                                .set_tooltip(Some(tray::tray_tooltip_text(count)));
+                        }
+                    }
+                });
+            }
+
+            let config_for_window = Arc::clone(&config);
+
             // 保存状态
             app.manage(AppState {
                 db,
                 config,
-                _clipboard_monitor: clipboard_monitor,
+                clipboard_monitor,
                 tray_handles,
+                tray_tooltip_dirty,
+                session_limit,
+                event_batch,
+                backfill,
             });
 
             if let Some(main_window) = app.get_webview_window("main") {
                 let window_handle = main_window.clone();
-                main_window.on_window_event(move |event| {
-                    if let WindowEvent::CloseRequested { api, .. } = event {
-                        api.prevent_close();
-                        let _ = window_handle.hide();
-                        let _ = window_handle.set_skip_taskbar(true);
+                main_window.on_window_event(move |event| match event {
+                    WindowEvent::CloseRequested { api, .. } => {
+                        let action = config_for_window.lock().unwrap().close_action();
+                        match action {
+                            WindowAction::Hide => {
+                                api.prevent_close();
+                                let _ = window_handle.hide();
+                                let _ = window_handle.set_skip_taskbar(true);
+                            }
+                            WindowAction::Minimize => {
+                                api.prevent_close();
+                                let _ = window_handle.minimize();
+                            }
+                            WindowAction::Quit => window_handle.app_handle().exit(0),
+                        }
                     }
+                    WindowEvent::Resized(_) => {
+                        let action = config_for_window.lock().unwrap().minimize_action();
+                        if action == WindowAction::Hide
+                            && window_handle.is_minimized().unwrap_or(false)
+                        {
+                            let _ = window_handle.unminimize();
+                            let _ = window_handle.hide();
+                            let _ = window_handle.set_skip_taskbar(true);
+                        }
+                    }
+                    _ => {}
                 });
             }
 
@@ -478,21 +1405,80 @@ pub fn run() {
         })
         .invoke_handler(tauri::generate_handler![
             get_history,
+            get_items_with_tag_ids,
+            get_untagged_items,
+            list_filter_presets,
+            apply_filter_preset,
+            get_ranked,
+            get_hourly_histogram,
             search_history,
+            search_regex,
+            find_items_referencing_path,
+            rebuild_search_text,
+            get_largest_items,
+            get_item_size,
+            get_next_item,
+            get_prev_item,
             add_clipboard_item,
+            import_external,
+            import_lines,
             toggle_favorite,
+            touch_item,
+            edit_item,
             delete_item,
             clear_history,
+            prune_images_older_than,
+            get_trash,
+            restore_from_trash,
             copy_to_clipboard,
+            current_clipboard_item,
+            make_digest,
+            export_items_to_folder,
+            export_redacted,
             add_tag,
             remove_tag,
+            delete_tag,
+            normalize_tag_case,
+            export_tags,
+            import_tags,
             get_all_tags,
             get_items_by_tag,
+            get_favorites_by_tag,
+            get_audit_log,
+            begin_batch,
+            end_batch,
+            set_session_limit,
+            clear_session_limit,
             get_config,
+            get_config_raw,
             update_config,
             set_autostart,
+            get_actual_autostart,
+            set_capture_paused,
+            get_capture_paused,
+            get_diagnostics,
+            repair_orphan_associations,
+            backup_database,
+            restore_database,
+            reset_config,
             reset_application,
         ])
-        .run(tauri::generate_context!())
-        .expect("error while running tauri application");
+        .build(tauri::generate_context!())
+        .expect("error while building tauri application")
+        .run(|app_handle, event| {
+            // 退出事件在进程结束前同步处理，清空完成后才会真正退出
+            if !matches!(event, RunEvent::Exit) {
+                return;
+            }
+            let Some(state) = app_handle.try_state::<AppState>() else {
+                return;
+            };
+
+            let action = state.config.lock().unwrap().exit_clear_action();
+            if let Some(action) = action {
+                if let Err(err) = state.db.clear_for_exit(action) {
+                    eprintln!("Failed to clear history on exit: {err:?}");
+                }
+            }
+        });
 }
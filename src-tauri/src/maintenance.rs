@@ -0,0 +1,284 @@
+use chrono::{DateTime, Duration as ChronoDuration, Utc};
+use std::sync::{
+    atomic::{AtomicBool, AtomicI64, Ordering},
+    Arc, Mutex,
+};
+use std::thread;
+use std::time::Duration;
+use tauri::Emitter;
+
+use crate::config::Config;
+use crate::database::{Database, MaintenanceOptions};
+
+/// 调度线程检查是否需要维护的间隔
+const TICK_INTERVAL: Duration = Duration::from_secs(60);
+/// 距离最近一次剪切板写入至少这么久才算空闲
+const IDLE_THRESHOLD_SECS: i64 = 120;
+
+/// 后台补齐每批处理的记录数
+const BACKFILL_BATCH_SIZE: usize = 500;
+/// 两批之间的停顿，让出数据库锁给前台操作
+const BACKFILL_BATCH_PAUSE: Duration = Duration::from_millis(50);
+
+/// 后台补齐派生列的运行器，保证同一时间最多只有一个补齐线程在推进游标
+pub struct Backfill {
+    running: AtomicBool,
+}
+
+/// 补齐线程持有的运行标记，线程结束时释放
+struct BackfillClaim(Arc<Backfill>);
+
+impl Drop for BackfillClaim {
+    fn drop(&mut self) {
+        self.0.running.store(false, Ordering::Release);
+    }
+}
+
+impl Backfill {
+    pub fn new() -> Self {
+        Self {
+            running: AtomicBool::new(false),
+        }
+    }
+
+    /// 占用运行标记；已有补齐线程在运行时返回 None
+    fn try_claim(self: &Arc<Self>) -> Option<BackfillClaim> {
+        self.running
+            .compare_exchange(false, true, Ordering::AcqRel, Ordering::Acquire)
+            .ok()
+            .map(|_| BackfillClaim(Arc::clone(self)))
+    }
+
+    /// 启动后台补齐线程：分批为旧记录补齐哈希、时间戳秒数与搜索文本，
+    /// 每批完成后发送 `backfill-progress` 事件，已完成时直接退出。
+    /// 已有补齐线程在运行时不再启动新线程（它会继续处理剩余记录），返回 false
+    pub fn start<R: tauri::Runtime>(
+        self: &Arc<Self>,
+        app_handle: tauri::AppHandle<R>,
+        db: Arc<Database>,
+    ) -> bool {
+        let Some(claim) = self.try_claim() else {
+            return false;
+        };
+
+        thread::spawn(move || {
+            let _claim = claim;
+            loop {
+                match db.is_backfill_done() {
+                    Ok(true) => break,
+                    Ok(false) => {}
+                    Err(err) => {
+                        eprintln!("Failed to read backfill state: {err:?}");
+                        break;
+                    }
+                }
+
+                match db.backfill_batch(BACKFILL_BATCH_SIZE) {
+                    Ok(progress) => {
+                        if let Err(err) = app_handle.emit("backfill-progress", progress) {
+                            eprintln!("Failed to emit backfill-progress event: {err:?}");
+                        }
+                        if progress.done {
+                            break;
+                        }
+                    }
+                    Err(err) => {
+                        eprintln!("Background backfill failed: {err:?}");
+                        break;
+                    }
+                }
+
+                thread::sleep(BACKFILL_BATCH_PAUSE);
+            }
+        });
+        true
+    }
+}
+
+/// 判断距离上次维护是否已经过了配置的间隔；从未运行过时视为到期
+pub fn maintenance_due(
+    last_run: Option<DateTime<Utc>>,
+    now: DateTime<Utc>,
+    interval_hours: i64,
+) -> bool {
+    if interval_hours <= 0 {
+        return false;
+    }
+
+    match last_run {
+        Some(last) => now - last >= ChronoDuration::hours(interval_hours),
+        None => true,
+    }
+}
+
+/// 判断当前是否处于空闲状态（最近没有新的剪切板记录写入）
+pub fn is_idle(last_activity: Option<DateTime<Utc>>, now: DateTime<Utc>) -> bool {
+    match last_activity {
+        Some(last) => now - last >= ChronoDuration::seconds(IDLE_THRESHOLD_SECS),
+        None => true,
+    }
+}
+
+/// 后台维护调度器
+pub struct MaintenanceScheduler {
+    last_activity: AtomicI64,
+}
+
+impl MaintenanceScheduler {
+    pub fn new() -> Self {
+        Self {
+            last_activity: AtomicI64::new(0),
+        }
+    }
+
+    /// 记录一次写入活动，推迟维护直到重新空闲
+    pub fn record_activity(&self) {
+        self.last_activity
+            .store(Utc::now().timestamp(), Ordering::Relaxed);
+    }
+
+    fn last_activity(&self) -> Option<DateTime<Utc>> {
+        match self.last_activity.load(Ordering::Relaxed) {
+            0 => None,
+            secs => DateTime::from_timestamp(secs, 0),
+        }
+    }
+
+    /// 启动调度线程，到期且空闲时执行维护并发送 `maintenance-ran` 事件
+    pub fn start<R: tauri::Runtime>(
+        self: &Arc<Self>,
+        app_handle: tauri::AppHandle<R>,
+        db: Arc<Database>,
+        config: Arc<Mutex<Config>>,
+    ) {
+        let scheduler = Arc::clone(self);
+
+        thread::spawn(move || {
+            // 上次维护时间保存在数据库中，重启后不会立即重复维护
+            let mut last_run = db.last_maintenance_run().unwrap_or_else(|err| {
+                eprintln!("Failed to read last maintenance time: {err:?}");
+                None
+            });
+
+            loop {
+                thread::sleep(TICK_INTERVAL);
+
+                let (interval_hours, options) = match config.lock() {
+                    Ok(cfg) => (
+                        cfg.maintenance_interval_hours,
+                        MaintenanceOptions {
+                            retention_days: cfg.retention_days,
+                            trash_retention_minutes: cfg.trash_retention_minutes,
+                            audit_retention_days: cfg.audit_log_retention_days,
+                        },
+                    ),
+                    Err(_) => continue,
+                };
+
+                let now = Utc::now();
+                if !maintenance_due(last_run, now, interval_hours)
+                    || !is_idle(scheduler.last_activity(), now)
+                {
+                    continue;
+                }
+
+                last_run = Some(now);
+                if let Err(err) = db.record_maintenance_run(now) {
+                    eprintln!("Failed to record maintenance time: {err:?}");
+                }
+
+                match db.run_maintenance(options) {
+                    Ok(summary) => {
+                        if let Err(err) = app_handle.emit("maintenance-ran", summary) {
+                            eprintln!("Failed to emit maintenance-ran event: {err:?}");
+                        }
+                    }
+                    Err(err) => {
+                        eprintln!("Background maintenance failed: {err:?}");
+                    }
+                }
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(secs: i64) -> DateTime<Utc> {
+        DateTime::from_timestamp(secs, 0).unwrap()
+    }
+
+    #[test]
+    fn backfill_runs_one_thread_at_a_time() {
+        let backfill = Arc::new(Backfill::new());
+
+        let first = backfill
+            .try_claim()
+            .expect("first start claims the backfill");
+        assert!(backfill.try_claim().is_none());
+
+        drop(first);
+        assert!(backfill.try_claim().is_some());
+    }
+
+    #[test]
+    fn maintenance_due_respects_interval() {
+        let now = at(1_700_000_000);
+
+        assert!(maintenance_due(None, now, 24));
+        assert!(!maintenance_due(
+            Some(now - ChronoDuration::hours(23)),
+            now,
+            24
+        ));
+        assert!(maintenance_due(
+            Some(now - ChronoDuration::hours(24)),
+            now,
+            24
+        ));
+        // 间隔为 0 表示关闭自动维护
+        assert!(!maintenance_due(None, now, 0));
+    }
+
+    #[test]
+    fn idle_after_threshold() {
+        let now = at(1_700_000_000);
+
+        assert!(is_idle(None, now));
+        assert!(!is_idle(Some(now - ChronoDuration::seconds(30)), now));
+        assert!(is_idle(
+            Some(now - ChronoDuration::seconds(IDLE_THRESHOLD_SECS)),
+            now
+        ));
+    }
+
+    #[test]
+    fn last_run_survives_reopen() {
+        let path = std::env::temp_dir().join(format!(
+            "cat-clipboard-maintenance-{}.db",
+            std::process::id()
+        ));
+        let _ = std::fs::remove_file(&path);
+        let now = at(1_700_000_000);
+
+        {
+            let db = Database::new(path.clone()).unwrap();
+            assert_eq!(db.last_maintenance_run().unwrap(), None);
+            db.record_maintenance_run(now).unwrap();
+        }
+
+        let reopened = Database::new(path.clone()).unwrap();
+        let last_run = reopened.last_maintenance_run().unwrap();
+        assert_eq!(last_run, Some(now));
+        assert!(!maintenance_due(
+            last_run,
+            now + ChronoDuration::hours(1),
+            24
+        ));
+
+        drop(reopened);
+        let _ = std::fs::remove_file(path);
+    }
+}
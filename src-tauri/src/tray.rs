@@ -0,0 +1,168 @@
+use std::path::Path;
+
+use crate::config::Config;
+
+/// 暂停记录时托盘图标保留的不透明度（百分比）
+const PAUSED_ALPHA_PERCENT: u32 = 55;
+
+/// 托盘状态图标所在的资源子目录
+pub(crate) const TRAY_ICON_DIR: &str = "icons";
+
+/// 与记录状态对应的托盘图标文件名（随安装包一同发布）
+pub(crate) fn tray_icon_name(paused: bool) -> &'static str {
+    if paused {
+        "tray-paused.png"
+    } else {
+        "tray-active.png"
+    }
+}
+
+/// 从 `dir` 读取与记录状态对应的托盘图标，返回 RGBA 像素与宽高；
+/// 文件缺失或无法解码时返回 None，由调用方回退到默认图标
+pub(crate) fn load_tray_icon(dir: &Path, paused: bool) -> Option<(Vec<u8>, u32, u32)> {
+    let path = dir.join(tray_icon_name(paused));
+    if !path.is_file() {
+        return None;
+    }
+    let rgba = image::open(&path).ok()?.to_rgba8();
+    let (width, height) = rgba.dimensions();
+    Some((rgba.into_raw(), width, height))
+}
+
+/// 没有状态图标资源时的回退：记录中沿用默认图标，
+/// 暂停时转为灰度并降低不透明度
+pub(crate) fn tray_icon_rgba(base: &[u8], paused: bool) -> Vec<u8> {
+    if !paused {
+        return base.to_vec();
+    }
+
+    base.chunks_exact(4)
+        .flat_map(|pixel| {
+            let [red, green, blue, alpha] = [pixel[0], pixel[1], pixel[2], pixel[3]].map(u32::from);
+            let gray = ((red * 299 + green * 587 + blue * 114) / 1000) as u8;
+            let alpha = (alpha * PAUSED_ALPHA_PERCENT / 100) as u8;
+            [gray, gray, gray, alpha]
+        })
+        .collect()
+}
+
+fn theme_display_label(theme: &str) -> &'static str {
+    match theme {
+        "light" => "浅色",
+        "dark" => "深色",
+        _ => "自动",
+    }
+}
+
+pub(crate) fn theme_menu_label(theme: &str) -> String {
+    format!("切换主题（当前：{}）", theme_display_label(theme))
+}
+
+/// 托盘菜单中随配置变化的部分
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct TrayMenuState {
+    pub theme_label: String,
+    pub autostart_checked: bool,
+}
+
+impl TrayMenuState {
+    pub(crate) fn from_config(config: &Config) -> Self {
+        Self {
+            theme_label: theme_menu_label(&config.theme),
+            autostart_checked: config.auto_start,
+        }
+    }
+}
+
+/// 托盘提示文字：显示当前的记录数量
+pub(crate) fn tray_tooltip_text(count: i64) -> String {
+    if count <= 0 {
+        "Cat History — 暂无记录".to_string()
+    } else {
+        format!("Cat History — {count} 条")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ORANGE: [u8; 4] = [240, 120, 40, 255];
+    const TRANSPARENT: [u8; 4] = [0, 0, 0, 0];
+
+    #[test]
+    fn active_icon_is_unchanged() {
+        let base = [ORANGE, TRANSPARENT].concat();
+        assert_eq!(tray_icon_rgba(&base, false), base);
+    }
+
+    #[test]
+    fn paused_icon_is_gray_and_dimmed() {
+        let base = [ORANGE, TRANSPARENT].concat();
+        let paused = tray_icon_rgba(&base, true);
+
+        assert_eq!(paused.len(), base.len());
+        let (gray, alpha) = (paused[0], paused[3]);
+        assert_eq!([paused[1], paused[2]], [gray, gray]);
+        assert!(alpha < ORANGE[3] && alpha > 0);
+        assert_eq!(&paused[4..], &TRANSPARENT);
+        assert_ne!(paused, base);
+    }
+
+    #[test]
+    fn icon_name_follows_capture_state() {
+        assert_eq!(tray_icon_name(false), "tray-active.png");
+        assert_eq!(tray_icon_name(true), "tray-paused.png");
+    }
+
+    #[test]
+    fn missing_icon_assets_fall_back() {
+        let dir = std::env::temp_dir().join(format!("cat-clipboard-tray-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+
+        assert_eq!(load_tray_icon(&dir, false), None);
+
+        image::RgbaImage::from_raw(2, 1, [ORANGE, TRANSPARENT].concat())
+            .unwrap()
+            .save(dir.join(tray_icon_name(true)))
+            .unwrap();
+        assert_eq!(
+            load_tray_icon(&dir, true),
+            Some(([ORANGE, TRANSPARENT].concat(), 2, 1))
+        );
+        assert_eq!(load_tray_icon(&dir, false), None);
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn menu_state_follows_reset_config() {
+        let custom = Config {
+            theme: "dark".to_string(),
+            auto_start: true,
+            ..Config::default()
+        };
+        assert_eq!(
+            TrayMenuState::from_config(&custom),
+            TrayMenuState {
+                theme_label: "切换主题（当前：深色）".to_string(),
+                autostart_checked: true,
+            }
+        );
+
+        let defaults = Config::default().sanitized();
+        let menu = TrayMenuState::from_config(&defaults);
+        assert_eq!(menu.theme_label, theme_menu_label(&defaults.theme));
+        assert_eq!(menu.autostart_checked, defaults.auto_start);
+        assert_ne!(menu, TrayMenuState::from_config(&custom));
+    }
+
+    #[test]
+    fn tooltip_shows_item_count() {
+        assert_eq!(tray_tooltip_text(0), "Cat History — 暂无记录");
+        assert_eq!(tray_tooltip_text(-1), "Cat History — 暂无记录");
+        assert_eq!(tray_tooltip_text(1), "Cat History — 1 条");
+        assert_eq!(tray_tooltip_text(1234), "Cat History — 1234 条");
+    }
+}
@@ -82,6 +82,7 @@ function App() {
   const [savingConfig, setSavingConfig] = useState(false);
   const [autostartBusy, setAutostartBusy] = useState(false);
   const [resettingApp, setResettingApp] = useState(false);
+  const [resettingConfig, setResettingConfig] = useState(false);
   const searchRef = useRef("");
   const themeMediaQuery = useRef<MediaQueryList | null>(null);
   const themeMediaListener = useRef<((event: MediaQueryListEvent) => void) | null>(null);
@@ -329,6 +330,52 @@ function App() {
     [config?.max_history_items, showStatus]
   );
 
+  const applyResetConfig = useCallback(
+    (fresh: AppConfig) => {
+      setConfig(fresh);
+      setHotkeyDraft(fresh.hotkey);
+      setMaxHistoryDraft(String(fresh.max_history_items));
+      applyTheme(fresh.theme);
+    },
+    [applyTheme]
+  );
+
+  // 后端恢复默认配置后发送 config-reset；更新 config 会触发快捷键重新注册
+  useEffect(() => {
+    let unlistenReset: UnlistenFn | undefined;
+
+    const bind = async () => {
+      unlistenReset = await listen<AppConfig>("config-reset", (event) => {
+        applyResetConfig(event.payload);
+      });
+    };
+
+    bind();
+
+    return () => {
+      if (unlistenReset) unlistenReset();
+    };
+  }, [applyResetConfig]);
+
+  const handleResetConfig = useCallback(async () => {
+    if (resettingConfig) return;
+    if (!window.confirm("确定要恢复默认设置吗？历史记录与标签会被保留。")) {
+      return;
+    }
+
+    setResettingConfig(true);
+    try {
+      const fresh = await invoke<AppConfig>("reset_config");
+      applyResetConfig(fresh);
+      showStatus("已恢复默认设置", 2200);
+    } catch (error) {
+      console.error("Failed to reset config:", error);
+      showStatus("恢复默认设置失败，请稍后重试", 2400);
+    } finally {
+      setResettingConfig(false);
+    }
+  }, [applyResetConfig, resettingConfig, showStatus]);
+
   const handleResetApplication = useCallback(async () => {
     if (resettingApp) return;
     if (!window.confirm("确定要重置吗？所有历史记录、标签与设置将被清除。")) {
@@ -411,7 +458,7 @@ function App() {
       }
 
       try {
-        await invoke("copy_to_clipboard", { content: item.content });
+        await invoke("copy_to_clipboard", { content: item.content, itemId: item.id });
         window.setTimeout(() => {
           void loadHistory({ silent: true });
         }, 200);
@@ -516,11 +563,20 @@ function App() {
 
   useEffect(() => {
     let unlistenHistory: UnlistenFn | undefined;
+    let unlistenRefreshed: UnlistenFn | undefined;
     let unlistenClipboard: UnlistenFn | undefined;
 
     const setup = async () => {
       await loadHistory();
-      unlistenHistory = await listen<number>("history-updated", () => {
+      unlistenHistory = await listen<number | null>("history-updated", () => {
+        const keyword = searchRef.current.trim();
+        if (keyword) {
+          void searchHistory(keyword);
+        } else {
+          void loadHistory({ silent: true });
+        }
+      });
+      unlistenRefreshed = await listen<number>("history-refreshed", () => {
         const keyword = searchRef.current.trim();
         if (keyword) {
           void searchHistory(keyword);
@@ -544,6 +600,9 @@ function App() {
       if (unlistenHistory) {
         unlistenHistory();
       }
+      if (unlistenRefreshed) {
+        unlistenRefreshed();
+      }
       if (unlistenClipboard) {
         unlistenClipboard();
       }
@@ -950,6 +1009,20 @@ function App() {
                 <p className="settings-hint">使用 + 连接组合键，支持 Ctrl、Alt、Shift、Win 等键位。</p>
               </section>
 
+              <section className="settings-group">
+                <div className="settings-group__heading">
+                  <h3>恢复默认设置</h3>
+                  <span>仅恢复设置，保留历史记录与标签</span>
+                </div>
+                <button
+                  className="btn"
+                  onClick={() => void handleResetConfig()}
+                  disabled={resettingConfig}
+                >
+                  {resettingConfig ? "恢复中…" : "恢复默认设置"}
+                </button>
+              </section>
+
               <section className="settings-group settings-group--danger">
                 <div className="settings-group__heading">
                   <h3>重置应用</h3>